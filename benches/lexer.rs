@@ -0,0 +1,76 @@
+// Lexer throughput benchmark: `cargo bench --bench lexer`
+//
+// Lexes generated programs of increasing size with the streaming lexer and reports the time per
+// byte and the heap memory allocated on top of the source. Time per byte should stay flat as the
+// input grows. Tokens are not retained, so the extra memory is only the symbol table, which grows
+// with the number of distinct identifiers (every generated statement introduces new ones).
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use fikra::fikra_tokenization::tokenization::tokens::Lexer;
+
+struct CountingAlloc;
+
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let live = LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+// Builds a program of at least `size` bytes that exercises every kind of token
+fn generate_source(size: usize) -> String {
+    let mut source = String::with_capacity(size + 256);
+    let mut i = 0;
+    while source.len() < size {
+        source.push_str(&format!(
+            "/// value number {i}\nlet value_{i} = (0x{i:x} + {i}_000u64) * 3.5e-2 / 7; // trailing comment\n\
+             {{ let text_{i} = \"line\\t{i}\\n\"; return value_{i} >= 12 && !false; }}\n/* block /* nested */ comment */\n"
+        ));
+        i += 1;
+    }
+    source
+}
+
+fn main() {
+    let mut baseline_ns_per_byte = None;
+
+    for megabytes in [1, 2, 4, 8, 16] {
+        let source = generate_source(megabytes * 1024 * 1024);
+
+        PEAK_BYTES.store(LIVE_BYTES.load(Ordering::Relaxed), Ordering::Relaxed);
+        let live_before = LIVE_BYTES.load(Ordering::Relaxed);
+        let start = Instant::now();
+
+        let mut token_count = 0usize;
+        for token in Lexer::new(&source) {
+            black_box(token.expect("generated source should lex cleanly"));
+            token_count += 1;
+        }
+
+        let elapsed = start.elapsed();
+        let extra_bytes = PEAK_BYTES.load(Ordering::Relaxed) - live_before;
+        let ns_per_byte = elapsed.as_nanos() as f64 / source.len() as f64;
+        let relative = ns_per_byte / *baseline_ns_per_byte.get_or_insert(ns_per_byte);
+
+        println!(
+            "{:>2} MiB: {:>9} tokens in {:>9.2?}  {:>6.2} ns/byte ({:.2}x of 1 MiB)  peak extra heap {} bytes",
+            megabytes, token_count, elapsed, ns_per_byte, relative, extra_bytes
+        );
+    }
+}
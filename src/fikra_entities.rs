@@ -1,5 +1,11 @@
 
-#[derive(Debug, Clone, PartialEq)]
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+
+use crate::fikra_symbols::Symbol;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TokenType {
     // Keywords
     Return,     // 'return' keyword for returning from a function
@@ -8,6 +14,11 @@ pub enum TokenType {
     Else,       // 'else' keyword for alternative in conditional statements
     While,      // 'while' keyword for while loops
     For,        // 'for' keyword for for loops
+    Break,      // 'break' keyword for leaving a loop
+    Continue,   // 'continue' keyword for skipping to the next loop iteration
+    In,         // 'in' keyword between a for loop variable and its range
+    Mut,        // 'mut' keyword for declaring a variable that can be reassigned
+    Fn,         // 'fn' keyword for function declarations
     
     // Literals
     IntLit,     // Integer literal of unspecified size
@@ -21,6 +32,7 @@ pub enum TokenType {
     UInt64Lit,  // 64-bit unsigned integer literal
     FloatLit,   // Floating-point literal
     StringLit,  // String literal
+    CharLit,    // Character literal, usable as its integer code point
     BoolLit,    // Boolean literal (true or false)
 
     // Identifiers
@@ -33,6 +45,11 @@ pub enum TokenType {
     Slash,      // Division operator '/'
     Percent,    // Modulo operator '%'
     Eq,         // Assignment operator '='
+    PlusEq,     // Compound assignment operator '+='
+    MinusEq,    // Compound assignment operator '-='
+    StarEq,     // Compound assignment operator '*='
+    SlashEq,    // Compound assignment operator '/='
+    PercentEq,  // Compound assignment operator '%='
     EqEq,       // Equality comparison operator '=='
     NotEq,      // Inequality comparison operator '!='
     Lt,         // Less than operator '<'
@@ -43,6 +60,8 @@ pub enum TokenType {
     Or,         // Logical OR operator '||'
     Not,        // Logical NOT operator '!'
     Caret,      // Exponentiation operator '^'
+    DotDot,     // Range operator '..', excluding the end
+    DotDotEq,   // Range operator '..=', including the end
     
     // Delimiters
     Semi,       // Semicolon ';' for statement termination
@@ -59,16 +78,60 @@ pub enum TokenType {
     EOF,        // End of file marker
 }
 
+// Every keyword the language recognizes, with the token it lexes to.
+// `true` and `false` are listed for completeness; they lex to BoolLit with a Boolean value.
+pub const KEYWORDS: &[(&str, TokenType)] = &[
+    ("return", TokenType::Return),
+    ("let", TokenType::Let),
+    ("if", TokenType::If),
+    ("else", TokenType::Else),
+    ("while", TokenType::While),
+    ("for", TokenType::For),
+    ("break", TokenType::Break),
+    ("continue", TokenType::Continue),
+    ("in", TokenType::In),
+    ("mut", TokenType::Mut),
+    ("fn", TokenType::Fn),
+    ("true", TokenType::BoolLit),
+    ("false", TokenType::BoolLit),
+];
+
+// Words set aside for planned language features. They cannot be used as identifiers.
+pub const RESERVED_WORDS: &[&str] = &[
+    "loop", "match", "const", "struct", "enum", "type", "use", "pub", "as",
+];
+
+// Text-carrying values borrow from the source where they can, so lexing does not copy it
 #[derive(Debug, Clone, PartialEq)]
-pub struct Token {
+pub struct Token<'src> {
     pub _type: TokenType,
-    pub value: Option<TokenValue>,
+    pub value: Option<TokenValue<'src>>,
     pub line: usize,
     pub column: usize,
+    pub span: Range<usize>, // Byte range of the token in the source
+    pub doc: Option<Cow<'src, str>>, // Text of the `///` doc comments directly preceding the token
+    pub leading_trivia: Vec<Trivia<'src>>, // Only filled in by the lossless lexer
+    pub trailing_trivia: Vec<Trivia<'src>>, // Only filled in by the lossless lexer
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    Whitespace,
+    LineComment,
+    DocComment,
+    BlockComment,
+    Skipped, // Source text that could not be lexed
+}
+
+// Source text between tokens, kept by the lossless lexer
 #[derive(Debug, Clone, PartialEq)]
-pub enum TokenValue {
+pub struct Trivia<'src> {
+    pub kind: TriviaKind,
+    pub text: &'src str,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenValue<'src> {
     Int8(i8),
     Int16(i16),
     Int32(i32),
@@ -80,7 +143,9 @@ pub enum TokenValue {
     Float32(f32),
     Float64(f64),
     Boolean(bool),
-    Identifier(String),
+    Identifier(Symbol),
+    Str(Cow<'src, str>),
+    Char(char),
 }
 
 #[derive(Debug, PartialEq, PartialOrd)]
@@ -98,14 +163,114 @@ pub enum Precedence {
 }
 
 
-impl Token {
-    pub fn new(_type: TokenType, value: Option<TokenValue>, line: usize, column: usize) -> Self {
-        Token { _type, value, line, column }
+impl<'src> Token<'src> {
+    pub fn new(_type: TokenType, value: Option<TokenValue<'src>>, line: usize, column: usize) -> Self {
+        Token {
+            _type,
+            value,
+            line,
+            column,
+            span: 0..0,
+            doc: None,
+            leading_trivia: Vec::new(),
+            trailing_trivia: Vec::new(),
+        }
     }
 }
 
 
+impl TokenValue<'_> {
+    // Builds the integer value for a literal of the given type, or None if `n` does not fit its width
+    pub fn integer(_type: &TokenType, n: i128) -> Option<TokenValue<'static>> {
+        match _type {
+            TokenType::Int8Lit => i8::try_from(n).ok().map(TokenValue::Int8),
+            TokenType::Int16Lit => i16::try_from(n).ok().map(TokenValue::Int16),
+            TokenType::IntLit | TokenType::Int32Lit => i32::try_from(n).ok().map(TokenValue::Int32),
+            TokenType::Int64Lit => i64::try_from(n).ok().map(TokenValue::Int64),
+            TokenType::UInt8Lit => u8::try_from(n).ok().map(TokenValue::UInt8),
+            TokenType::UInt16Lit => u16::try_from(n).ok().map(TokenValue::UInt16),
+            TokenType::UInt32Lit => u32::try_from(n).ok().map(TokenValue::UInt32),
+            TokenType::UInt64Lit => u64::try_from(n).ok().map(TokenValue::UInt64),
+            _ => None,
+        }
+    }
+
+    // The value of an integer literal, widened so that every width fits.
+    // Character literals give their code point, and booleans 1 or 0.
+    pub fn as_integer(&self) -> Option<i128> {
+        match *self {
+            TokenValue::Char(c) => Some(u32::from(c).into()),
+            TokenValue::Boolean(b) => Some(b.into()),
+            TokenValue::Int8(n) => Some(n.into()),
+            TokenValue::Int16(n) => Some(n.into()),
+            TokenValue::Int32(n) => Some(n.into()),
+            TokenValue::Int64(n) => Some(n.into()),
+            TokenValue::UInt8(n) => Some(n.into()),
+            TokenValue::UInt16(n) => Some(n.into()),
+            TokenValue::UInt32(n) => Some(n.into()),
+            TokenValue::UInt64(n) => Some(n.into()),
+            _ => None,
+        }
+    }
+}
+
 impl  TokenType {
+    pub fn from_keyword(word: &str) -> Option<TokenType> {
+        KEYWORDS.iter().find(|(text, _)| *text == word).map(|(_, _type)| *_type)
+    }
+
+    // The source spelling of a keyword token. BoolLit has two spellings and is not a keyword here.
+    pub fn keyword(&self) -> Option<&'static str> {
+        if *self == TokenType::BoolLit {
+            return None;
+        }
+        KEYWORDS.iter().find(|(_, _type)| _type == self).map(|(text, _)| *text)
+    }
+
+    // The source spelling of an operator or delimiter token
+    pub fn symbol(&self) -> Option<&'static str> {
+        let symbol = match self {
+            TokenType::Plus => "+",
+            TokenType::Minus => "-",
+            TokenType::Star => "*",
+            TokenType::Slash => "/",
+            TokenType::Percent => "%",
+            TokenType::Eq => "=",
+            TokenType::PlusEq => "+=",
+            TokenType::MinusEq => "-=",
+            TokenType::StarEq => "*=",
+            TokenType::SlashEq => "/=",
+            TokenType::PercentEq => "%=",
+            TokenType::EqEq => "==",
+            TokenType::NotEq => "!=",
+            TokenType::Lt => "<",
+            TokenType::LtEq => "<=",
+            TokenType::Gt => ">",
+            TokenType::GtEq => ">=",
+            TokenType::And => "&&",
+            TokenType::Or => "||",
+            TokenType::Not => "!",
+            TokenType::Caret => "^",
+            TokenType::DotDot => "..",
+            TokenType::DotDotEq => "..=",
+            TokenType::Semi => ";",
+            TokenType::Comma => ",",
+            TokenType::Dot => ".",
+            TokenType::OpenParen => "(",
+            TokenType::CloseParen => ")",
+            TokenType::OpenBrace => "{",
+            TokenType::CloseBrace => "}",
+            TokenType::OpenBracket => "[",
+            TokenType::CloseBracket => "]",
+            _ => return None,
+        };
+        Some(symbol)
+    }
+
+    pub fn is_reserved_word(word: &str) -> bool {
+        RESERVED_WORDS.contains(&word)
+    }
+
     pub fn get_precedence(&self) -> Precedence {
         match self {
             TokenType::Eq => Precedence::Assignment,
@@ -134,8 +299,76 @@ impl  TokenType {
         }
     }
 
-    fn is_binary_op(&self) -> bool {
-        matches!(self, TokenType::Plus | TokenType::Star /* Add other binary operators */)
+    pub fn is_int_lit(&self) -> bool {
+        matches!(
+            self,
+            TokenType::IntLit
+                | TokenType::Int8Lit
+                | TokenType::Int16Lit
+                | TokenType::Int32Lit
+                | TokenType::Int64Lit
+                | TokenType::UInt8Lit
+                | TokenType::UInt16Lit
+                | TokenType::UInt32Lit
+                | TokenType::UInt64Lit
+        )
+    }
+
+    pub fn is_literal(&self) -> bool {
+        self.is_int_lit() || matches!(self, TokenType::FloatLit | TokenType::StringLit | TokenType::CharLit | TokenType::BoolLit)
+    }
+
+    // Keywords that begin a statement, where the parser can pick up again after a syntax error
+    pub fn is_statement_keyword(&self) -> bool {
+        matches!(
+            self,
+            TokenType::Return
+                | TokenType::Let
+                | TokenType::If
+                | TokenType::While
+                | TokenType::For
+                | TokenType::Break
+                | TokenType::Continue
+                | TokenType::Fn
+        )
+    }
+
+    // Operators the parser builds binary expressions for
+    pub fn is_binary_op(&self) -> bool {
+        matches!(
+            self,
+            TokenType::Plus
+                | TokenType::Minus
+                | TokenType::Star
+                | TokenType::Slash
+                | TokenType::Percent
+                | TokenType::EqEq
+                | TokenType::NotEq
+                | TokenType::Lt
+                | TokenType::LtEq
+                | TokenType::Gt
+                | TokenType::GtEq
+                | TokenType::And
+                | TokenType::Or
+                | TokenType::Caret
+        )
+    }
+
+    // For a compound assignment operator, the binary operator it applies
+    pub fn compound_assignment_op(&self) -> Option<TokenType> {
+        match self {
+            TokenType::PlusEq => Some(TokenType::Plus),
+            TokenType::MinusEq => Some(TokenType::Minus),
+            TokenType::StarEq => Some(TokenType::Star),
+            TokenType::SlashEq => Some(TokenType::Slash),
+            TokenType::PercentEq => Some(TokenType::Percent),
+            _ => None,
+        }
+    }
+
+    // `a ^ b ^ c` groups as `a ^ (b ^ c)`; every other binary operator groups to the left
+    pub fn is_right_associative(&self) -> bool {
+        *self == TokenType::Caret
     }
 }
 
@@ -172,4 +405,75 @@ impl Precedence {
     }
 
 
-}
\ No newline at end of file
+}
+
+// Keywords and symbols are shown as they are spelled, in backticks; other tokens by what they are
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TokenType::IntLit
+            | TokenType::Int8Lit
+            | TokenType::Int16Lit
+            | TokenType::Int32Lit
+            | TokenType::Int64Lit
+            | TokenType::UInt8Lit
+            | TokenType::UInt16Lit
+            | TokenType::UInt32Lit
+            | TokenType::UInt64Lit => "integer literal",
+            TokenType::FloatLit => "floating-point literal",
+            TokenType::StringLit => "string literal",
+            TokenType::CharLit => "character literal",
+            TokenType::BoolLit => "boolean literal",
+            TokenType::Ident => "identifier",
+            TokenType::EOF => "end of input",
+            // Listed one by one so that a new token type has to be given a description here
+            TokenType::Return
+            | TokenType::Let
+            | TokenType::If
+            | TokenType::Else
+            | TokenType::While
+            | TokenType::For
+            | TokenType::Break
+            | TokenType::Continue
+            | TokenType::In
+            | TokenType::Mut
+            | TokenType::Fn
+            | TokenType::Plus
+            | TokenType::Minus
+            | TokenType::Star
+            | TokenType::Slash
+            | TokenType::Percent
+            | TokenType::Eq
+            | TokenType::PlusEq
+            | TokenType::MinusEq
+            | TokenType::StarEq
+            | TokenType::SlashEq
+            | TokenType::PercentEq
+            | TokenType::EqEq
+            | TokenType::NotEq
+            | TokenType::Lt
+            | TokenType::LtEq
+            | TokenType::Gt
+            | TokenType::GtEq
+            | TokenType::And
+            | TokenType::Or
+            | TokenType::Not
+            | TokenType::Caret
+            | TokenType::DotDot
+            | TokenType::DotDotEq
+            | TokenType::Semi
+            | TokenType::Comma
+            | TokenType::Dot
+            | TokenType::OpenParen
+            | TokenType::CloseParen
+            | TokenType::OpenBrace
+            | TokenType::CloseBrace
+            | TokenType::OpenBracket
+            | TokenType::CloseBracket => {
+                let text = self.keyword().or_else(|| self.symbol()).expect("keywords and symbols have a spelling");
+                return write!(f, "`{}`", text);
+            }
+        };
+        write!(f, "{}", name)
+    }
+}
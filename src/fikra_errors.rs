@@ -1,14 +1,81 @@
 
 use std::error::Error;
 use std::fmt;
+use std::ops::Range;
+
+use crate::fikra_entities::TokenType;
+use crate::fikra_symbols::Symbol;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexErrorKind {
+    UnexpectedCharacter(char),
+    IntegerOutOfRange(String, String),
+    InvalidIntegerLiteral(String),
+    InvalidIntegerSuffix(String),
+    InvalidDigit(char, u32),
+    InvalidFloatLiteral(String),
+    UnterminatedBlockComment,
+    UnterminatedString,
+    InvalidEscape(String),
+    InvalidUnicodeEscape(String),
+    EmptyCharLiteral,
+    UnterminatedChar,
+    MultipleCharsInLiteral,
+    ReservedWord(String),
+}
+
+// A lexical error, located by line/column and by byte range in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub line: usize,
+    pub column: usize,
+    pub span: Range<usize>,
+}
+
+// The construct the parser was in the middle of when it hit an error
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Construct {
+    Program,
+    ReturnStatement,
+    LetStatement,
+    AssignStatement,
+    CallStatement,
+    Scope,
+    IfStatement,
+    WhileStatement,
+    ForStatement,
+    BreakStatement,
+    ContinueStatement,
+    FunctionDeclaration,
+    ParenExpression,
+    CallExpression,
+    ArrayLiteral,
+    IndexExpression,
+}
+
+// Something the parser would have accepted in place of the offending token
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Expected {
+    Token(TokenType),
+    Expression,
+    Statement,
+}
+
+// A syntax error at the token found at `line`/`column`. `found` is None at the end of the input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub expected: Vec<Expected>,
+    pub found: Option<TokenType>,
+    pub construct: Construct,
+    pub line: usize,
+    pub column: usize,
+}
 
 #[derive(Debug)]
 pub enum ParseError {
-    UnexpectedToken(String),
-    UnexpectedEndOfInput,
-    InvalidStatement,
-    InvalidExpression,
-    // Add more specific error types as needed
+    Syntax(Vec<SyntaxError>),
+    Lexical(Vec<LexError>),
 }
 
 
@@ -20,17 +87,119 @@ pub enum GeneratorError {
     InvalidExpression,
     InvalidIntegerValue,
     IdentifierAlreadyUsed,
-    UndefinedVariable(String),
+    UndefinedVariable(Symbol),
+    // `break` or `continue` outside of a loop, at the given line and column
+    OutsideLoop(&'static str, usize, usize),
+    // Assignment to a variable declared without `mut`, at the given line and column
+    ImmutableAssignment(Symbol, usize, usize),
+    UndefinedFunction(Symbol),
+    FunctionAlreadyDefined(Symbol),
+    // Function called with the wrong number of arguments: the name, then expected and given counts
+    ArgumentCount(Symbol, usize, usize),
+    InvalidMain(&'static str),
+    NotAnArray(Symbol),
+    // An array variable used other than by indexing it or passing it to `len`
+    ArrayAsValue(Symbol),
+    // A misplaced or malformed array literal: the reason, then its line and column
+    InvalidArray(&'static str, usize, usize),
+}
+
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexErrorKind::UnexpectedCharacter(c) => write!(f, "Unexpected character: {}", c),
+            LexErrorKind::IntegerOutOfRange(lit, width) => write!(f, "Integer literal {} does not fit in {}", lit, width),
+            LexErrorKind::InvalidIntegerLiteral(lit) => write!(f, "Invalid integer literal: {}", lit),
+            LexErrorKind::InvalidIntegerSuffix(suffix) => write!(f, "Invalid integer suffix: {}", suffix),
+            LexErrorKind::InvalidDigit(c, radix) => write!(f, "Invalid digit {} for a base {} literal", c, radix),
+            LexErrorKind::InvalidFloatLiteral(lit) => write!(f, "Invalid floating-point literal: {}", lit),
+            LexErrorKind::EmptyCharLiteral => write!(f, "Empty character literal"),
+            LexErrorKind::UnterminatedChar => write!(f, "Unterminated character literal"),
+            LexErrorKind::MultipleCharsInLiteral => write!(f, "Character literal may only contain one character"),
+            LexErrorKind::ReservedWord(word) => write!(f, "{} is a reserved word and cannot be used as an identifier", word),
+            LexErrorKind::UnterminatedBlockComment => write!(f, "Unterminated block comment"),
+            LexErrorKind::UnterminatedString => write!(f, "Unterminated string literal"),
+            LexErrorKind::InvalidEscape(e) => write!(f, "Invalid escape sequence: \\{}", e),
+            LexErrorKind::InvalidUnicodeEscape(e) => write!(f, "Invalid unicode escape: \\u{{{}}}", e),
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}, column {}", self.kind, self.line, self.column)
+    }
+}
+
+impl Error for LexError {}
+
+impl fmt::Display for Construct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Construct::Program => write!(f, "program"),
+            Construct::ReturnStatement => write!(f, "return statement"),
+            Construct::LetStatement => write!(f, "let statement"),
+            Construct::AssignStatement => write!(f, "assignment"),
+            Construct::CallStatement => write!(f, "call statement"),
+            Construct::Scope => write!(f, "scope"),
+            Construct::IfStatement => write!(f, "if statement"),
+            Construct::WhileStatement => write!(f, "while statement"),
+            Construct::ForStatement => write!(f, "for statement"),
+            Construct::BreakStatement => write!(f, "break statement"),
+            Construct::ContinueStatement => write!(f, "continue statement"),
+            Construct::FunctionDeclaration => write!(f, "function declaration"),
+            Construct::ParenExpression => write!(f, "parenthesized expression"),
+            Construct::CallExpression => write!(f, "function call"),
+            Construct::ArrayLiteral => write!(f, "array literal"),
+            Construct::IndexExpression => write!(f, "index expression"),
+        }
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Token(_type) => write!(f, "{}", _type),
+            Expected::Expression => write!(f, "expression"),
+            Expected::Statement => write!(f, "statement"),
+        }
+    }
+}
+
+// e.g. "expected `;` after let statement at 3:14, found `}`"
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let expected: Vec<String> = self.expected.iter().map(|e| e.to_string()).collect();
+        match expected.split_last() {
+            Some((last, [])) => write!(f, "expected {}", last)?,
+            Some((last, rest)) => write!(f, "expected one of {} or {}", rest.join(", "), last)?,
+            None => write!(f, "unexpected token")?,
+        }
+
+        // A statement is complete once only its terminator is missing
+        let after = self.expected == [Expected::Token(TokenType::Semi)];
+        write!(f, " {} {} at {}:{}, found ", if after { "after" } else { "in" }, self.construct, self.line, self.column)?;
+        match self.found {
+            Some(_type) => write!(f, "{}", _type),
+            None => write!(f, "{}", TokenType::EOF),
+        }
+    }
 }
 
+impl Error for SyntaxError {}
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ParseError::UnexpectedToken(token) => write!(f, "Unexpected token: {}", token),
-            ParseError::UnexpectedEndOfInput => write!(f, "Unexpected end of input"),
-            ParseError::InvalidStatement => write!(f, "Invalid statement"),
-            ParseError::InvalidExpression => write!(f, "Invalid expression"),
+            ParseError::Syntax(errors) => {
+                let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", messages.join("\n"))
+            }
+            ParseError::Lexical(errors) => {
+                let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+                write!(f, "{}", messages.join("\n"))
+            }
             // Add more error messages as needed
         }
     }
@@ -49,6 +218,19 @@ impl fmt::Display for GeneratorError {
             GeneratorError::InvalidIntegerValue => write!(f, "Invalid integer or missing integer"),
             GeneratorError::IdentifierAlreadyUsed => write!(f, "Identifier already used"),
             GeneratorError::UndefinedVariable(e) => write!(f, "Variable {} is undefined", e),
+            GeneratorError::OutsideLoop(keyword, line, column) => write!(f, "`{}` outside of a loop at {}:{}", keyword, line, column),
+            GeneratorError::ImmutableAssignment(e, line, column) => {
+                write!(f, "Cannot assign to immutable variable {} at {}:{}; declare it with `let mut`", e, line, column)
+            }
+            GeneratorError::UndefinedFunction(e) => write!(f, "Function {} is undefined", e),
+            GeneratorError::FunctionAlreadyDefined(e) => write!(f, "Function {} is already defined", e),
+            GeneratorError::ArgumentCount(e, expected, given) => {
+                write!(f, "Function {} takes {} argument(s) but {} were given", e, expected, given)
+            }
+            GeneratorError::InvalidMain(reason) => write!(f, "Invalid main function: {}", reason),
+            GeneratorError::NotAnArray(e) => write!(f, "Variable {} is not an array", e),
+            GeneratorError::ArrayAsValue(e) => write!(f, "Array {} can only be indexed or passed to len", e),
+            GeneratorError::InvalidArray(reason, line, column) => write!(f, "Invalid array literal at {}:{}: {}", line, column, reason),
         }
     }
 }
@@ -1,52 +1,114 @@
-use crate::fikra_entities::{TokenType, TokenValue};
-use crate::fikra_parser::node::{ExprVar, NodeBinExprVariant, NodeExpr, NodeProg, NodeStmt, StmtVariant};
+use crate::fikra_entities::{Token, TokenType, TokenValue};
+use crate::fikra_parser::node::{ExprVar, NodeExprArray, NodeExprArrayVariant, NodeExprCall, NodeBinExprVariant, NodeExpr, NodeUnaryExprVariant, NodeProg, NodeStmt, NodeStmtScope, StmtVariant};
 use crate::fikra_errors::GeneratorError;
-use crate::fikra_entities::TokenValue::Int32;
+use crate::fikra_symbols::Symbol;
 use std::collections::HashMap;
 
 pub struct Generator<'arena> {
     root: NodeProg<'arena>,
+    // Number of parameters of every declared function
+    functions: HashMap<Symbol, usize>,
 }
 
 impl<'arena> Generator<'arena> {
     pub fn new(root: NodeProg<'arena>) -> Self {
-        Generator { root }
+        Generator { root, functions: HashMap::new() }
     }
 
-    pub fn generate_program(&self) -> Result<String, GeneratorError> {
-        
-        let mut asm_builder = AsmBuilder::new();
-        let mut stack = Stack::new();
-        
+    // Top-level statements other than function declarations make up an implicit `main`, unless
+    // `main` is declared. The process exits with the value `main` returns.
+    pub fn generate_program(mut self) -> Result<String, GeneratorError> {
+        let mut declarations = Vec::new();
+        let mut top_level = Vec::new();
         for stmt in &self.root.statements {
-            self.generate_statement(stmt, &mut asm_builder,&mut stack)?;
+            match &stmt.variant {
+                StmtVariant::VariantEleven(declaration) => {
+                    let symbol = identifier(&declaration.ident)?;
+                    if self.functions.insert(symbol, declaration.params.len()).is_some() {
+                        return Err(GeneratorError::FunctionAlreadyDefined(symbol));
+                    }
+                    declarations.push(declaration);
+                },
+                _ => top_level.push(*stmt),
+            }
+        }
+
+        let main = Symbol::intern("main");
+        let declares_main = self.functions.contains_key(&main);
+        match self.functions.get(&main) {
+            Some(0) if !top_level.is_empty() => return Err(GeneratorError::InvalidMain("statements outside of a function cannot be combined with `fn main`")),
+            Some(0) => {},
+            Some(_) => return Err(GeneratorError::InvalidMain("main cannot take parameters")),
+            None => {
+                self.functions.insert(main, 0);
+            },
         }
+
+        let mut asm_builder = AsmBuilder::new();
+        asm_builder.add_jump("call", &function_label(main));
+        asm_builder.add_instruction("mov rdi, rax");
         asm_builder.add_instruction("mov rax, 60");
-        asm_builder.add_instruction("mov rdi, 0");
         asm_builder.add_instruction("syscall");
+
+        for declaration in declarations {
+            let symbol = identifier(&declaration.ident)?;
+            let params = declaration.params.iter().map(identifier).collect::<Result<Vec<_>, _>>()?;
+            self.generate_function(symbol, &params, &declaration.body.statements, &mut asm_builder)?;
+        }
+        if !declares_main {
+            self.generate_function(main, &[], &top_level, &mut asm_builder)?;
+        }
         Ok(asm_builder.build())
     }
 
+    // Arguments are pushed left to right before the call, so the last one is just above the
+    // return address. The prologue copies them into the function's own slots.
+    fn generate_function(&self, symbol: Symbol, params: &[Symbol], statements: &[&NodeStmt], asm: &mut AsmBuilder) -> Result<(), GeneratorError> {
+        let mut stack = Stack::new();
+
+        asm.add_label(&function_label(symbol));
+        asm.add_instruction("push rbp");
+        asm.add_instruction("mov rbp, rsp");
+        for (i, param) in params.iter().enumerate() {
+            if stack.map_variables.contains_key(param) {
+                return Err(GeneratorError::IdentifierAlreadyUsed);
+            }
+            stack.map_variables.insert(*param, Var {stack_loc: stack.current_size(), mutable: false, length: None});
+            let offset = 16 + (params.len() - 1 - i) * 8;
+            asm.add_instruction(&stack.push(&format!("QWORD [rbp + {}]", offset)));
+        }
+
+        for stmt in statements {
+            self.generate_statement(stmt, asm, &mut stack)?;
+        }
+
+        // Falling off the end returns 0
+        asm.add_instruction("mov rax, 0");
+        generate_epilogue(asm);
+        Ok(())
+    }
+
     fn generate_statement(&self, node_stmt: &NodeStmt, asm: &mut AsmBuilder,stack: &mut Stack) -> Result<(), GeneratorError> {
 
         match &node_stmt.variant {
             StmtVariant::VariantOne(stmt) => {
-                self.generate_expression(&stmt.expr, asm,stack)?;
-                asm.add_instruction("mov rax, 60");
-                asm.add_instruction(&stack.pop("rdi"));
-                asm.add_instruction("syscall");
+                self.generate_expression(stmt.expr, asm,stack)?;
+                asm.add_instruction(&stack.pop("rax"));
+                generate_epilogue(asm);
                 Ok(())
             },
             StmtVariant::VariantTwo(stmt) => {
                 match (&stmt.ident._type, &stmt.ident.value) {
-                    (TokenType::Ident, Some(TokenValue::Identifier(ident_str))) => {
-                        if stack.map_variables.contains_key(ident_str) {
+                    (TokenType::Ident, Some(TokenValue::Identifier(symbol))) => {
+                        if stack.map_variables.contains_key(symbol) {
                            
                             Err(GeneratorError::InvalidStatement)
+                        } else if let ExprVar::VariantSeven(node_expr_array) = &stmt.expr.variant {
+                            self.generate_array(*symbol, stmt.mutable, node_expr_array, asm, stack)
                         } else {
                             
-                            stack.map_variables.insert(ident_str.to_string(), Var {stack_loc: stack.current_size()});
-                            self.generate_expression(&stmt.expr, asm, stack)?;
+                            stack.map_variables.insert(*symbol, Var {stack_loc: stack.current_size(), mutable: stmt.mutable, length: None});
+                            self.generate_expression(stmt.expr, asm, stack)?;
                             
                             Ok(())
                         }
@@ -56,24 +118,184 @@ impl<'arena> Generator<'arena> {
                 }
                
             },
-            StmtVariant::VariantThree(stmt) =>{
+            StmtVariant::VariantThree(stmt) => self.generate_scope(stmt, asm, stack),
+            StmtVariant::VariantFive(stmt) => {
+                let else_label = asm.unique_label("else");
+                let end_label = asm.unique_label("if_end");
 
-                  stack.begin_scope();
-                   for stm in stmt.statements.iter(){
-                        self.generate_statement(stm, asm, stack)?;
-                   }
-                   stack.end_scope(asm);
+                self.generate_expression(stmt.condition, asm, stack)?;
+                asm.add_instruction(&stack.pop("rax"));
+                asm.add_instruction("test rax, rax");
+                asm.add_jump("jz", &else_label);
+
+                // Both branches start and end with the stack as it was before the statement
+                self.generate_scope(&stmt.body, asm, stack)?;
+                asm.add_jump("jmp", &end_label);
+
+                asm.add_label(&else_label);
+                if let Some(else_stmt) = stmt.else_stmt {
+                    self.generate_statement(else_stmt, asm, stack)?;
+                }
+                asm.add_label(&end_label);
+                Ok(())
+            },
+            StmtVariant::VariantSix(stmt) => {
+                let start_label = asm.unique_label("while_start");
+                let end_label = asm.unique_label("while_end");
+
+                asm.add_label(&start_label);
+                self.generate_expression(stmt.condition, asm, stack)?;
+                asm.add_instruction(&stack.pop("rax"));
+                asm.add_instruction("test rax, rax");
+                asm.add_jump("jz", &end_label);
+
+                stack.begin_loop(&start_label, &end_label);
+                self.generate_scope(&stmt.body, asm, stack)?;
+                stack.end_loop();
+                asm.add_jump("jmp", &start_label);
+
+                asm.add_label(&end_label);
+                Ok(())
+            },
+            StmtVariant::VariantNine(stmt) => {
+                let symbol = match &stmt.ident.value {
+                    Some(TokenValue::Identifier(symbol)) => *symbol,
+                    _ => return Err(GeneratorError::InvalidStatement),
+                };
+                if stack.map_variables.contains_key(&symbol) {
+                    return Err(GeneratorError::IdentifierAlreadyUsed);
+                }
+
+                let start_label = asm.unique_label("for_start");
+                let step_label = asm.unique_label("for_step");
+                let end_label = asm.unique_label("for_end");
+
+                // The counter, the end and the step each get one slot for the whole loop, in a
+                // scope of their own so that only the body sees the counter
+                stack.begin_scope();
+                let counter = stack.current_size();
+                self.generate_expression(stmt.start, asm, stack)?;
+                let end = stack.current_size();
+                self.generate_expression(stmt.end, asm, stack)?;
+                let step = match stmt.step {
+                    Some(step_expr) => {
+                        let step = stack.current_size();
+                        self.generate_expression(step_expr, asm, stack)?;
+                        let error_label = asm.runtime_error("for loop step must be positive");
+                        asm.add_instruction("cmp QWORD [rsp], 0");
+                        asm.add_jump("jle", &error_label);
+                        Some(step)
+                    },
+                    None => None,
+                };
+                stack.map_variables.insert(symbol, Var {stack_loc: counter, mutable: false, length: None});
+
+                asm.add_label(&start_label);
+                asm.add_instruction(&format!("mov rax, {}", stack.slot(counter)));
+                asm.add_instruction(&format!("cmp rax, {}", stack.slot(end)));
+                asm.add_jump(if stmt.inclusive { "jg" } else { "jge" }, &end_label);
+
+                stack.begin_loop(&step_label, &end_label);
+                self.generate_scope(&stmt.body, asm, stack)?;
+                stack.end_loop();
+
+                asm.add_label(&step_label);
+                match step {
+                    Some(step) => {
+                        asm.add_instruction(&format!("mov rax, {}", stack.slot(step)));
+                        asm.add_instruction(&format!("add {}, rax", stack.slot(counter)));
+                    },
+                    None => asm.add_instruction(&format!("add {}, 1", stack.slot(counter))),
+                }
+                asm.add_jump("jmp", &start_label);
 
+                asm.add_label(&end_label);
+                stack.end_scope(asm);
                 Ok(())
             },
+            StmtVariant::VariantTen(stmt) => {
+                let symbol = match &stmt.ident.value {
+                    Some(TokenValue::Identifier(symbol)) => *symbol,
+                    _ => return Err(GeneratorError::InvalidStatement),
+                };
+                let var = stack.map_variables.get(&symbol).ok_or(GeneratorError::UndefinedVariable(symbol))?;
+                if !var.mutable {
+                    return Err(GeneratorError::ImmutableAssignment(symbol, stmt.ident.line, stmt.ident.column));
+                }
+                let (stack_loc, length) = (var.stack_loc, var.length);
+
+                match (stmt.index, length, stmt.op) {
+                    (None, None, _) => {
+                        self.generate_expression(stmt.expr, asm, stack)?;
+                        asm.add_instruction(&stack.pop("rax"));
+                        // Same slot that generate_identifier_expression reads the variable from
+                        asm.add_instruction(&format!("mov {}, rax", stack.slot(stack_loc)));
+                    },
+                    // `a[i] op= e` evaluates and checks the index once, keeping it on the stack
+                    // while the element is updated
+                    (Some(index), Some(length), Some(op)) => {
+                        self.generate_expression(index, asm, stack)?;
+                        asm.add_instruction("mov rax, QWORD [rsp]");
+                        generate_bounds_check(&stmt.ident, length, asm)?;
+                        asm.add_instruction(&format!("mov rax, {}", stack.element(stack_loc, length)));
+                        asm.add_instruction(&stack.push("rax"));
+                        self.generate_expression(stmt.expr, asm, stack)?;
+                        generate_operator(op, asm, stack)?;
+                        asm.add_instruction(&stack.pop("rbx"));
+                        asm.add_instruction(&stack.pop("rax"));
+                        asm.add_instruction(&format!("mov {}, rbx", stack.element(stack_loc, length)));
+                    },
+                    (Some(index), Some(length), None) => {
+                        self.generate_expression(stmt.expr, asm, stack)?;
+                        self.generate_expression(index, asm, stack)?;
+                        asm.add_instruction(&stack.pop("rax"));
+                        generate_bounds_check(&stmt.ident, length, asm)?;
+                        asm.add_instruction(&stack.pop("rbx"));
+                        asm.add_instruction(&format!("mov {}, rbx", stack.element(stack_loc, length)));
+                    },
+                    (None, Some(_), _) => return Err(GeneratorError::ArrayAsValue(symbol)),
+                    (Some(_), None, _) => return Err(GeneratorError::NotAnArray(symbol)),
+                }
+                Ok(())
+            },
+            StmtVariant::VariantSeven(stmt) => {
+                let frame = stack.loops.last().ok_or(GeneratorError::OutsideLoop("break", stmt.keyword.line, stmt.keyword.column))?;
+                let label = frame.break_label.clone();
+                stack.release_to(frame.depth, asm);
+                asm.add_jump("jmp", &label);
+                Ok(())
+            },
+            StmtVariant::VariantEight(stmt) => {
+                let frame = stack.loops.last().ok_or(GeneratorError::OutsideLoop("continue", stmt.keyword.line, stmt.keyword.column))?;
+                let label = frame.continue_label.clone();
+                stack.release_to(frame.depth, asm);
+                asm.add_jump("jmp", &label);
+                Ok(())
+            },
+            StmtVariant::VariantTwelve(stmt) => {
+                self.generate_call(stmt.call, asm, stack)?;
+                stack.discard(1, asm); // The returned value is not used
+                Ok(())
+            },
+            // Functions are declared at the top level, where generate_program picks them out
             _ => Err(GeneratorError::InvalidStatement),
         }
     }
 
+    fn generate_scope(&self, node_scope: &NodeStmtScope, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+        stack.begin_scope();
+        for stm in node_scope.statements.iter(){
+            self.generate_statement(stm, asm, stack)?;
+        }
+        stack.end_scope(asm);
+
+        Ok(())
+    }
+
     fn generate_expression(&self, node_expr: &NodeExpr, asm: &mut AsmBuilder,stack: &mut Stack) -> Result<(), GeneratorError> {
         match &node_expr.variant {
             ExprVar::VariantOne(node_expres_int_lit) => {
-                if let Some(Int32(n)) = &node_expres_int_lit.int_lit.value {
+                if let Some(n) = node_expres_int_lit.int_lit.value.as_ref().and_then(TokenValue::as_integer) {
                     asm.add_instruction(&format!("mov rax, {}", n));
                     asm.add_instruction(&stack.push("rax"));
                     Ok(())
@@ -83,8 +305,8 @@ impl<'arena> Generator<'arena> {
             },
             ExprVar::VariantTwo(n) => {
 
-                if let (TokenType::Ident, Some(TokenValue::Identifier(ident_str))) = (&n.ident._type, &n.ident.value){
-                    self.generate_identifier_expression(ident_str, asm, stack)
+                if let (TokenType::Ident, Some(TokenValue::Identifier(symbol))) = (&n.ident._type, &n.ident.value){
+                    self.generate_identifier_expression(*symbol, asm, stack)
                 }else{
                     Err(GeneratorError::InvalidStatement)
                 }
@@ -95,81 +317,365 @@ impl<'arena> Generator<'arena> {
                 // For parenthesized expressions, we simply generate code for the inner expression
                 self.generate_expression(node_expr_paren.expr, asm, stack)
             },
-            _ => Err(GeneratorError::InvalidExpression),
+            ExprVar::VariantFive(node_unary_expr) => self.generate_unary_expression(&node_unary_expr.variant, asm, stack),
+            ExprVar::VariantSix(node_expr_call) => self.generate_call(node_expr_call, asm, stack),
+            ExprVar::VariantSeven(node_expr_array) => {
+                let bracket = &node_expr_array.open_bracket;
+                Err(GeneratorError::InvalidArray("array literals can only initialize a variable", bracket.line, bracket.column))
+            },
+            ExprVar::VariantEight(node_expr_index) => {
+                let symbol = identifier(&node_expr_index.ident)?;
+                let var = stack.map_variables.get(&symbol).ok_or(GeneratorError::UndefinedVariable(symbol))?;
+                let (stack_loc, length) = (var.stack_loc, var.length.ok_or(GeneratorError::NotAnArray(symbol))?);
+
+                self.generate_expression(node_expr_index.index, asm, stack)?;
+                asm.add_instruction(&stack.pop("rax"));
+                generate_bounds_check(&node_expr_index.ident, length, asm)?;
+                let element = stack.element(stack_loc, length);
+                asm.add_instruction(&stack.push(&element));
+                Ok(())
+            },
+        }
+    }
+
+    fn generate_call(&self, node_expr_call: &NodeExprCall, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+        let symbol = identifier(&node_expr_call.ident)?;
+        // `len` is a builtin unless the program declares its own
+        if symbol == Symbol::intern("len") && !self.functions.contains_key(&symbol) {
+            return self.generate_len(node_expr_call, asm, stack);
+        }
+        let arity = *self.functions.get(&symbol).ok_or(GeneratorError::UndefinedFunction(symbol))?;
+        if arity != node_expr_call.args.len() {
+            return Err(GeneratorError::ArgumentCount(symbol, arity, node_expr_call.args.len()));
+        }
+
+        for arg in &node_expr_call.args {
+            self.generate_expression(arg, asm, stack)?;
+        }
+        asm.add_jump("call", &function_label(symbol));
+        stack.discard(arity as u128, asm);
+        asm.add_instruction(&stack.push("rax"));
+        Ok(())
+    }
+
+    // Arrays live in one block of `length` slots, element 0 at the lowest address
+    fn generate_array(&self, symbol: Symbol, mutable: bool, node_expr_array: &NodeExprArray, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+        let bracket = &node_expr_array.open_bracket;
+        let length = match &node_expr_array.variant {
+            NodeExprArrayVariant::VariantOne(list) => list.elements.len() as u128,
+            NodeExprArrayVariant::VariantTwo(repeat) => constant_value(repeat.count)
+                .and_then(|count| u128::try_from(count).ok())
+                .ok_or(GeneratorError::InvalidArray("the length must be a non-negative integer constant", bracket.line, bracket.column))?,
+        };
+
+        let stack_loc = stack.current_size();
+        stack.claim(length, asm);
+        match &node_expr_array.variant {
+            NodeExprArrayVariant::VariantOne(list) => {
+                for (i, element) in list.elements.iter().enumerate() {
+                    self.generate_expression(element, asm, stack)?;
+                    asm.add_instruction(&stack.pop("rax"));
+                    asm.add_instruction(&format!("mov QWORD [rbp - {}], rax", (stack_loc + length - i as u128) * 8));
+                }
+            },
+            NodeExprArrayVariant::VariantTwo(repeat) => {
+                self.generate_expression(repeat.value, asm, stack)?;
+                asm.add_instruction(&stack.pop("rax"));
+                if length > 0 {
+                    asm.add_instruction(&format!("lea rdi, [rbp - {}]", (stack_loc + length) * 8));
+                    asm.add_instruction(&format!("mov rcx, {}", length));
+                    asm.add_instruction("rep stosq");
+                }
+            },
         }
+
+        // Declared once filled, so the elements cannot refer to the array itself
+        stack.map_variables.insert(symbol, Var {stack_loc, mutable, length: Some(length)});
+        Ok(())
+    }
+
+    fn generate_len(&self, node_expr_call: &NodeExprCall, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+        let len = identifier(&node_expr_call.ident)?;
+        let symbol = match node_expr_call.args.as_slice() {
+            [arg] => match &arg.variant {
+                ExprVar::VariantTwo(node_expr_ident) => identifier(&node_expr_ident.ident)?,
+                _ => return Err(GeneratorError::InvalidExpression),
+            },
+            args => return Err(GeneratorError::ArgumentCount(len, 1, args.len())),
+        };
+        let var = stack.map_variables.get(&symbol).ok_or(GeneratorError::UndefinedVariable(symbol))?;
+        let length = var.length.ok_or(GeneratorError::NotAnArray(symbol))?;
+
+        asm.add_instruction(&format!("mov rax, {}", length));
+        asm.add_instruction(&stack.push("rax"));
+        Ok(())
     }
 
-    fn generate_identifier_expression(&self, ident_str: &str, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
-        if let Some(stack_loc_var) = stack.map_variables.get(ident_str) {
-            let offset = ((stack.current_size() - stack_loc_var.stack_loc) - 1) * 8;
-            let instruction = format!("QWORD [rsp + {}]", offset);
+    fn generate_identifier_expression(&self, symbol: Symbol, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+        if let Some(stack_loc_var) = stack.map_variables.get(&symbol) {
+            if stack_loc_var.length.is_some() {
+                return Err(GeneratorError::ArrayAsValue(symbol));
+            }
+            let instruction = stack.slot(stack_loc_var.stack_loc);
             asm.add_instruction(&stack.push(&instruction));
             Ok(())
         } else {
-            Err(GeneratorError::UndefinedVariable(ident_str.to_string()))
+            Err(GeneratorError::UndefinedVariable(symbol))
         }
     }
 
-    fn generate_binary_expression(&self, variant: &NodeBinExprVariant, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+    fn generate_unary_expression(&self, variant: &NodeUnaryExprVariant, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
         match variant {
-            NodeBinExprVariant::VariantOne(node_bin_expr_add) => {
-                self.generate_expression(node_bin_expr_add.lhs, asm, stack)?;
-                self.generate_expression(node_bin_expr_add.rhs, asm, stack)?;
-    
+            NodeUnaryExprVariant::VariantOne(node_unary_expr_neg) => {
+                self.generate_expression(node_unary_expr_neg.expr, asm, stack)?;
+
                 asm.add_instruction(&stack.pop("rax"));
-                asm.add_instruction(&stack.pop("rbx"));
-                asm.add_instruction("add rax, rbx");
+                asm.add_instruction("neg rax");
                 asm.add_instruction(&stack.push("rax"));
-    
                 Ok(())
             },
-            NodeBinExprVariant::VariantTwo(node_bin_expr_mul) => {
-                self.generate_expression(node_bin_expr_mul.lhs, asm, stack)?;
-                self.generate_expression(node_bin_expr_mul.rhs, asm, stack)?;
-    
-                asm.add_instruction(&stack.pop("rax"));
-                asm.add_instruction(&stack.pop("rbx"));
-                asm.add_instruction("mul rbx");
-                asm.add_instruction(&stack.push("rax"));
+            NodeUnaryExprVariant::VariantTwo(node_unary_expr_not) => {
+                self.generate_expression(node_unary_expr_not.expr, asm, stack)?;
 
+                asm.add_instruction(&stack.pop("rax"));
+                asm.add_instruction("xor rbx, rbx");
+                asm.add_instruction("test rax, rax");
+                asm.add_instruction("sete bl"); // 1 only when the operand is 0
+                asm.add_instruction(&stack.push("rbx"));
                 Ok(())
             },
-            NodeBinExprVariant::VariantThree(node_bin_expr_sub) => {
-                self.generate_expression(node_bin_expr_sub.lhs, asm, stack)?;
-                self.generate_expression(node_bin_expr_sub.rhs, asm, stack)?;
-                
-                asm.add_instruction(&stack.pop("rbx")); // Pop the right operand into rbx
-                asm.add_instruction(&stack.pop("rax")); // Pop the left operand into rax
-                asm.add_instruction("sub rax, rbx");    // Subtract rbx from rax
-                asm.add_instruction(&stack.push("rax")); // Push the result back onto the stack
+        }
+    }
+
+    fn generate_binary_expression(&self, variant: &NodeBinExprVariant, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+        match variant {
+            NodeBinExprVariant::VariantOne(node) => self.generate_arithmetic(node.lhs, node.rhs, TokenType::Plus, asm, stack),
+            NodeBinExprVariant::VariantTwo(node) => self.generate_arithmetic(node.lhs, node.rhs, TokenType::Star, asm, stack),
+            NodeBinExprVariant::VariantThree(node) => self.generate_arithmetic(node.lhs, node.rhs, TokenType::Minus, asm, stack),
+            NodeBinExprVariant::VariantFour(node) => self.generate_arithmetic(node.lhs, node.rhs, TokenType::Slash, asm, stack),
+            NodeBinExprVariant::VariantFive(node) => self.generate_comparison(node.lhs, node.rhs, "sete", asm, stack),
+            NodeBinExprVariant::VariantSix(node) => self.generate_comparison(node.lhs, node.rhs, "setne", asm, stack),
+            NodeBinExprVariant::VariantSeven(node) => self.generate_comparison(node.lhs, node.rhs, "setl", asm, stack),
+            NodeBinExprVariant::VariantEight(node) => self.generate_comparison(node.lhs, node.rhs, "setle", asm, stack),
+            NodeBinExprVariant::VariantNine(node) => self.generate_comparison(node.lhs, node.rhs, "setg", asm, stack),
+            NodeBinExprVariant::VariantTen(node) => self.generate_comparison(node.lhs, node.rhs, "setge", asm, stack),
+            // The right operand is skipped when the left one already decides the result
+            NodeBinExprVariant::VariantEleven(node) => self.generate_logical(node.lhs, node.rhs, "jz", asm, stack),
+            NodeBinExprVariant::VariantTwelve(node) => self.generate_logical(node.lhs, node.rhs, "jnz", asm, stack),
+            NodeBinExprVariant::VariantThirteen(node_bin_expr_pow) => {
+                self.generate_expression(node_bin_expr_pow.lhs, asm, stack)?;
+                self.generate_expression(node_bin_expr_pow.rhs, asm, stack)?;
+
+                let error_label = asm.runtime_error("negative exponent in integer exponentiation");
+                let loop_label = asm.unique_label("pow_loop");
+                let square_label = asm.unique_label("pow_square");
+                let end_label = asm.unique_label("pow_end");
+
+                asm.add_instruction(&stack.pop("rcx")); // Exponent
+                asm.add_instruction(&stack.pop("rbx")); // Base
+                asm.add_instruction("test rcx, rcx");
+                asm.add_jump("js", &error_label);
+                asm.add_instruction("mov rax, 1");
+
+                // Exponentiation by squaring: multiply in the base for every set bit of the exponent
+                asm.add_label(&loop_label);
+                asm.add_instruction("test rcx, rcx");
+                asm.add_jump("jz", &end_label);
+                asm.add_instruction("test rcx, 1");
+                asm.add_jump("jz", &square_label);
+                asm.add_instruction("imul rax, rbx");
+                asm.add_label(&square_label);
+                asm.add_instruction("imul rbx, rbx");
+                asm.add_instruction("shr rcx, 1");
+                asm.add_jump("jmp", &loop_label);
+
+                asm.add_label(&end_label);
+                asm.add_instruction(&stack.push("rax"));
                 Ok(())
             },
-            NodeBinExprVariant::VariantFour(node_bin_expr_div) => {
-                self.generate_expression(node_bin_expr_div.lhs, asm, stack)?;
-                self.generate_expression(node_bin_expr_div.rhs, asm, stack)?;
-    
-                asm.add_instruction(&stack.pop("rbx"));
-                asm.add_instruction(&stack.pop("rax"));
-                asm.add_instruction("div rbx");
-                asm.add_instruction(&stack.push("rax"));
+            NodeBinExprVariant::VariantFourteen(node_bin_expr_mod) => {
+                if let (Some(lhs), Some(rhs)) = (constant_value(node_bin_expr_mod.lhs), constant_value(node_bin_expr_mod.rhs)) {
+                    // Division by zero is left to fail at run time, like it does for `/`
+                    if let Some(remainder) = lhs.checked_rem(rhs) {
+                        asm.add_instruction(&format!("mov rax, {}", remainder));
+                        asm.add_instruction(&stack.push("rax"));
+                        return Ok(());
+                    }
+                }
 
-                Ok(())
+                self.generate_arithmetic(node_bin_expr_mod.lhs, node_bin_expr_mod.rhs, TokenType::Percent, asm, stack)
             },
-            _ => Err(GeneratorError::InvalidExpression)
         }
 
 
     }
 
+    fn generate_arithmetic(&self, lhs: &NodeExpr, rhs: &NodeExpr, op: TokenType, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+        self.generate_expression(lhs, asm, stack)?;
+        self.generate_expression(rhs, asm, stack)?;
+        generate_operator(op, asm, stack)
+    }
+
+    // Pushes 1 if `lhs` and `rhs` compare as `setcc` tests (signed), 0 otherwise
+    fn generate_comparison(&self, lhs: &NodeExpr, rhs: &NodeExpr, setcc: &str, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+        self.generate_expression(lhs, asm, stack)?;
+        self.generate_expression(rhs, asm, stack)?;
+
+        asm.add_instruction(&stack.pop("rbx"));
+        asm.add_instruction(&stack.pop("rax"));
+        asm.add_instruction("cmp rax, rbx");
+        asm.add_instruction(&format!("{} al", setcc));
+        asm.add_instruction("movzx rax, al");
+        asm.add_instruction(&stack.push("rax"));
+        Ok(())
+    }
+
+    // Pushes the truth (0 or 1) of `lhs`, or of `rhs` unless `jcc` jumps over it on the truth of `lhs`
+    fn generate_logical(&self, lhs: &NodeExpr, rhs: &NodeExpr, jcc: &str, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+        let end_label = asm.unique_label("logical_end");
+
+        self.generate_expression(lhs, asm, stack)?;
+        asm.add_instruction(&stack.pop("rax"));
+        asm.add_instruction("test rax, rax");
+        asm.add_instruction("setne al");
+        asm.add_instruction("movzx rax, al"); // Leaves the flags from test untouched
+        asm.add_jump(jcc, &end_label);
+
+        self.generate_expression(rhs, asm, stack)?;
+        asm.add_instruction(&stack.pop("rax"));
+        asm.add_instruction("test rax, rax");
+        asm.add_instruction("setne al");
+        asm.add_instruction("movzx rax, al");
+
+        asm.add_label(&end_label);
+        asm.add_instruction(&stack.push("rax"));
+        Ok(())
+    }
+
+}
+
+// Value of an expression made only of integer literals and operators on them. None when working
+// it out overflows or divides by zero, which is then left to happen at run time.
+fn constant_value(node_expr: &NodeExpr) -> Option<i64> {
+    match &node_expr.variant {
+        ExprVar::VariantOne(node_expr_int_lit) => {
+            let n = node_expr_int_lit.int_lit.value.as_ref().and_then(TokenValue::as_integer)?;
+            i64::try_from(n).ok()
+        },
+        ExprVar::VariantFour(node_expr_paren) => constant_value(node_expr_paren.expr),
+        ExprVar::VariantFive(node_unary_expr) => match &node_unary_expr.variant {
+            NodeUnaryExprVariant::VariantOne(node_unary_expr_neg) => constant_value(node_unary_expr_neg.expr)?.checked_neg(),
+            NodeUnaryExprVariant::VariantTwo(node_unary_expr_not) => Some(i64::from(constant_value(node_unary_expr_not.expr)? == 0)),
+        },
+        ExprVar::VariantThree(node_bin_expr) => match &node_bin_expr.variant {
+            NodeBinExprVariant::VariantOne(node) => constant_value(node.lhs)?.checked_add(constant_value(node.rhs)?),
+            NodeBinExprVariant::VariantTwo(node) => constant_value(node.lhs)?.checked_mul(constant_value(node.rhs)?),
+            NodeBinExprVariant::VariantThree(node) => constant_value(node.lhs)?.checked_sub(constant_value(node.rhs)?),
+            NodeBinExprVariant::VariantFour(node) => constant_value(node.lhs)?.checked_div(constant_value(node.rhs)?),
+            NodeBinExprVariant::VariantFive(node) => Some(i64::from(constant_value(node.lhs)? == constant_value(node.rhs)?)),
+            NodeBinExprVariant::VariantSix(node) => Some(i64::from(constant_value(node.lhs)? != constant_value(node.rhs)?)),
+            NodeBinExprVariant::VariantSeven(node) => Some(i64::from(constant_value(node.lhs)? < constant_value(node.rhs)?)),
+            NodeBinExprVariant::VariantEight(node) => Some(i64::from(constant_value(node.lhs)? <= constant_value(node.rhs)?)),
+            NodeBinExprVariant::VariantNine(node) => Some(i64::from(constant_value(node.lhs)? > constant_value(node.rhs)?)),
+            NodeBinExprVariant::VariantTen(node) => Some(i64::from(constant_value(node.lhs)? >= constant_value(node.rhs)?)),
+            // The right operand only counts when the left one does not decide the result
+            NodeBinExprVariant::VariantEleven(node) => match constant_value(node.lhs)? {
+                0 => Some(0),
+                _ => Some(i64::from(constant_value(node.rhs)? != 0)),
+            },
+            NodeBinExprVariant::VariantTwelve(node) => match constant_value(node.lhs)? {
+                0 => Some(i64::from(constant_value(node.rhs)? != 0)),
+                _ => Some(1),
+            },
+            // A negative exponent is left to fail at run time
+            NodeBinExprVariant::VariantThirteen(node) => {
+                let exponent = u32::try_from(constant_value(node.rhs)?).ok()?;
+                constant_value(node.lhs)?.checked_pow(exponent)
+            },
+            NodeBinExprVariant::VariantFourteen(node) => constant_value(node.lhs)?.checked_rem(constant_value(node.rhs)?),
+        },
+        ExprVar::VariantTwo(_) | ExprVar::VariantSix(_) | ExprVar::VariantSeven(_) | ExprVar::VariantEight(_) => None,
+    }
+}
+
+fn identifier(token: &Token) -> Result<Symbol, GeneratorError> {
+    match &token.value {
+        Some(TokenValue::Identifier(symbol)) => Ok(*symbol),
+        _ => Err(GeneratorError::InvalidStatement),
+    }
+}
+
+// Assembly label of a function. Names may hold any identifier character, so everything but
+// ASCII letters and digits is escaped, `_` as `__` and the rest as `_<hex code point>_`.
+fn function_label(symbol: Symbol) -> String {
+    let mut label = String::from("fn_");
+    for c in symbol.as_str().chars() {
+        match c {
+            c if c.is_ascii_alphanumeric() => label.push(c),
+            '_' => label.push_str("__"),
+            c => label.push_str(&format!("_{:x}_", u32::from(c))),
+        }
+    }
+    label
+}
+
+// With an index in rax, jumps to a runtime error unless it is below `length`. The comparison
+// is unsigned, so negative indices fail too.
+fn generate_bounds_check(ident: &Token, length: u128, asm: &mut AsmBuilder) -> Result<(), GeneratorError> {
+    let symbol = identifier(ident)?;
+    let error_label = asm.runtime_error(&format!("index out of bounds for array {} at line {}", symbol, ident.line));
+    asm.add_instruction(&format!("cmp rax, {}", length));
+    asm.add_jump("jae", &error_label);
+    Ok(())
+}
+
+// Replaces the two values on top of the stack, the right operand above the left one, with the
+// result of the arithmetic operator `op`
+fn generate_operator(op: TokenType, asm: &mut AsmBuilder, stack: &mut Stack) -> Result<(), GeneratorError> {
+    asm.add_instruction(&stack.pop("rbx")); // Right operand
+    asm.add_instruction(&stack.pop("rax")); // Left operand
+    let result = match op {
+        TokenType::Plus => {
+            asm.add_instruction("add rax, rbx");
+            "rax"
+        },
+        TokenType::Minus => {
+            asm.add_instruction("sub rax, rbx");
+            "rax"
+        },
+        TokenType::Star => {
+            asm.add_instruction("mul rbx");
+            "rax"
+        },
+        TokenType::Slash | TokenType::Percent => {
+            asm.add_instruction("cqo"); // Sign-extend rax into rdx for the signed division
+            asm.add_instruction("idiv rbx");
+            // The quotient, or the remainder with the sign of the dividend
+            if op == TokenType::Slash { "rax" } else { "rdx" }
+        },
+        _ => return Err(GeneratorError::InvalidExpression),
+    };
+    asm.add_instruction(&stack.push(result));
+    Ok(())
+}
+
+// Returns to the caller with the value in rax, whatever is on the stack
+fn generate_epilogue(asm: &mut AsmBuilder) {
+    asm.add_instruction("mov rsp, rbp");
+    asm.add_instruction("pop rbp");
+    asm.add_instruction("ret");
 }
 
 struct AsmBuilder {
     asm_string: String,
+    label_count: usize,
+    // Label and message of every runtime error the program can raise
+    runtime_errors: Vec<(String, String)>,
 }
 
 impl AsmBuilder {
     fn new() -> Self {
-        let mut builder = AsmBuilder { asm_string: String::new() };
+        let mut builder = AsmBuilder { asm_string: String::new(), label_count: 0, runtime_errors: Vec::new() };
         builder.add_directive("global _start");
         builder.add_directive("_start:");
         builder
@@ -186,23 +692,79 @@ impl AsmBuilder {
         self.asm_string.push('\n');
     }
 
-    fn build(self) -> String {
+    // A label name that no other call returns
+    fn unique_label(&mut self, name: &str) -> String {
+        self.label_count += 1;
+        format!("{}_{}", name, self.label_count)
+    }
+
+    // `jump` is `jmp` or a conditional jump such as `jz`
+    fn add_jump(&mut self, jump: &str, label: &str) {
+        self.add_instruction(&format!("{} {}", jump, label));
+    }
+
+    fn add_label(&mut self, label: &str) {
+        self.add_directive(&format!("{}:", label));
+    }
+
+    // Label to jump to in order to print `message` to stderr and exit with status 1.
+    // The code for each distinct message is emitted once, after the program.
+    fn runtime_error(&mut self, message: &str) -> String {
+        if let Some((label, _)) = self.runtime_errors.iter().find(|(_, text)| text == message) {
+            return label.clone();
+        }
+        let label = self.unique_label("runtime_error");
+        self.runtime_errors.push((label.clone(), message.to_string()));
+        label
+    }
+
+    fn build(mut self) -> String {
+        let runtime_errors = std::mem::take(&mut self.runtime_errors);
+        for (label, message) in &runtime_errors {
+            self.add_label(label);
+            self.add_instruction("mov rax, 1");
+            self.add_instruction("mov rdi, 2");
+            self.add_instruction(&format!("mov rsi, {}_message", label));
+            self.add_instruction(&format!("mov rdx, {}", message.len() + "runtime error: \n".len()));
+            self.add_instruction("syscall");
+            self.add_instruction("mov rax, 60");
+            self.add_instruction("mov rdi, 1");
+            self.add_instruction("syscall");
+        }
+
+        if !runtime_errors.is_empty() {
+            self.add_directive("section .data");
+            for (label, message) in &runtime_errors {
+                self.add_directive(&format!("{}_message: db \"runtime error: {}\", 10", label, message));
+            }
+        }
         self.asm_string
     }
 }
 
+// For an array, `stack_loc` is where its block of `length` slots starts
 struct Var{
     stack_loc: u128,
+    mutable: bool,
+    length: Option<u128>,
+}
+// Where `break` and `continue` jump to, and the stack size to return to before jumping
+struct LoopFrame {
+    continue_label: String,
+    break_label: String,
+    depth: u128,
 }
+
 struct Stack {
     index: u128,
-    map_variables: HashMap<String, Var>,
+    map_variables: HashMap<Symbol, Var>,
     scope_boundaries: Vec<u128>,
+    loops: Vec<LoopFrame>,
 }
 
 impl Stack {
     fn new() -> Self {
-        Stack { index: 0, map_variables: HashMap::new() ,scope_boundaries: Vec::new(),}
+        Stack { index: 0, map_variables: HashMap::new() ,scope_boundaries: Vec::new(), loops: Vec::new() }
     }
 
     fn push(&mut self, reg: &str) -> String {
@@ -239,4 +801,52 @@ impl Stack {
         }
     }
 
+    // Memory operand for the value pushed when the stack held `stack_loc` values, addressed from
+    // the frame base so that it does not move as the stack grows
+    fn slot(&self, stack_loc: u128) -> String {
+        format!("QWORD [rbp - {}]", (stack_loc + 1) * 8)
+    }
+
+    // Memory operand for the element of the array at `stack_loc` whose index is in rax
+    fn element(&self, stack_loc: u128, length: u128) -> String {
+        format!("QWORD [rbp + rax*8 - {}]", (stack_loc + length) * 8)
+    }
+
+    // Makes room for `count` values, to be filled in place
+    fn claim(&mut self, count: u128, asm: &mut AsmBuilder) {
+        if count > 0 {
+            asm.add_instruction(&format!("sub rsp, {}", count * 8));
+        }
+        self.index += count;
+    }
+
+    // Drops the `count` values on top of the stack
+    fn discard(&mut self, count: u128, asm: &mut AsmBuilder) {
+        if count > 0 {
+            asm.add_instruction(&format!("add rsp, {}", count * 8));
+        }
+        self.index -= count;
+    }
+
+    fn begin_loop(&mut self, continue_label: &str, break_label: &str) {
+        self.loops.push(LoopFrame {
+            continue_label: continue_label.to_string(),
+            break_label: break_label.to_string(),
+            depth: self.index,
+        });
+    }
+
+    fn end_loop(&mut self) {
+        self.loops.pop();
+    }
+
+    // Drops everything pushed since the stack held `depth` values, for a jump out of the scopes
+    // that pushed it. The scopes still release it themselves on the path that falls through.
+    fn release_to(&self, depth: u128, asm: &mut AsmBuilder) {
+        let values_to_pop = self.index - depth;
+        if values_to_pop > 0 {
+            asm.add_instruction(&format!("add rsp, {}", values_to_pop * 8));
+        }
+    }
+
 }
\ No newline at end of file
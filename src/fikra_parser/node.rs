@@ -1,30 +1,90 @@
 use typed_arena::Arena;
-use crate::fikra_entities::Token;
+use crate::fikra_entities::{Token, TokenType};
+use crate::fikra_errors::SyntaxError;
 
 pub struct AstArena<'arena> {
     pub expr_arena: Arena<NodeExpr<'arena>>,
     pub stmt_arena: Arena<NodeStmt<'arena>>,
     pub bin_expr_arena: Arena<NodeBinExpr<'arena>>,
     pub paren_expr_arena: Arena<NodeExprParen<'arena>>,
+    pub unary_expr_arena: Arena<NodeUnaryExpr<'arena>>,
+    pub call_expr_arena: Arena<NodeExprCall<'arena>>,
+    pub array_expr_arena: Arena<NodeExprArray<'arena>>,
+    pub index_expr_arena: Arena<NodeExprIndex<'arena>>,
 }
 
-pub struct NodeExprIntLit {
-    pub int_lit: Token,
+pub struct NodeExprIntLit<'arena> {
+    pub int_lit: Token<'arena>,
 }
 
-pub struct NodeExprIdent {
-   pub ident: Token,
+pub struct NodeExprIdent<'arena> {
+   pub ident: Token<'arena>,
 }
 
 pub struct NodeExprParen<'arena> {
     pub expr: &'arena NodeExpr<'arena>,
 }
 
+// `ident(args)`
+pub struct NodeExprCall<'arena> {
+    pub ident: Token<'arena>,
+    pub args: Vec<&'arena NodeExpr<'arena>>,
+}
+
+pub enum NodeExprArrayVariant<'arena> {
+    VariantOne(NodeExprArrayList<'arena>),
+    VariantTwo(NodeExprArrayRepeat<'arena>),
+}
+
+// `[a, b, c]`
+pub struct NodeExprArrayList<'arena> {
+    pub elements: Vec<&'arena NodeExpr<'arena>>,
+}
+
+// `[value; count]`
+pub struct NodeExprArrayRepeat<'arena> {
+    pub value: &'arena NodeExpr<'arena>,
+    pub count: &'arena NodeExpr<'arena>,
+}
+
+// `open_bracket` locates the literal for errors
+pub struct NodeExprArray<'arena> {
+    pub open_bracket: Token<'arena>,
+    pub variant: NodeExprArrayVariant<'arena>,
+}
+
+// `ident[index]`
+pub struct NodeExprIndex<'arena> {
+    pub ident: Token<'arena>,
+    pub index: &'arena NodeExpr<'arena>,
+}
+
 pub enum ExprVar<'arena> {
-    VariantOne(NodeExprIntLit),
-    VariantTwo(NodeExprIdent),
+    VariantOne(NodeExprIntLit<'arena>),
+    VariantTwo(NodeExprIdent<'arena>),
     VariantThree(&'arena NodeBinExpr<'arena>),
-    VariantFour(&'arena NodeExprParen<'arena>)
+    VariantFour(&'arena NodeExprParen<'arena>),
+    VariantFive(&'arena NodeUnaryExpr<'arena>),
+    VariantSix(&'arena NodeExprCall<'arena>),
+    VariantSeven(&'arena NodeExprArray<'arena>),
+    VariantEight(&'arena NodeExprIndex<'arena>),
+}
+
+pub enum NodeUnaryExprVariant<'arena> {
+    VariantOne(NodeUnaryExprNeg<'arena>),
+    VariantTwo(NodeUnaryExprNot<'arena>),
+}
+
+pub struct NodeUnaryExprNeg<'arena> {
+    pub expr: &'arena NodeExpr<'arena>,
+}
+
+pub struct NodeUnaryExprNot<'arena> {
+    pub expr: &'arena NodeExpr<'arena>,
+}
+
+pub struct NodeUnaryExpr<'arena> {
+    pub variant: NodeUnaryExprVariant<'arena>,
 }
 
 pub struct NodeStmtReturn<'arena> {
@@ -32,7 +92,18 @@ pub struct NodeStmtReturn<'arena> {
 }
 
 pub struct NodeStmtLet<'arena> {
-    pub ident: Token,
+    pub ident: Token<'arena>,
+    pub expr: &'arena NodeExpr<'arena>,
+    pub mutable: bool,
+}
+
+// `ident = expr;`, or `ident[index] = expr;` for an array element. Compound assignments to a
+// variable are parsed into this form, `x += 1` as `x = x + 1`. For an element that would
+// evaluate the index twice, so `a[i] += 1` keeps its operator (`+`) in `op` instead.
+pub struct NodeStmtAssign<'arena> {
+    pub ident: Token<'arena>,
+    pub index: Option<&'arena NodeExpr<'arena>>,
+    pub op: Option<TokenType>,
     pub expr: &'arena NodeExpr<'arena>,
 }
 
@@ -40,17 +111,85 @@ pub struct  NodeStmtScope<'arena>{
     pub statements: Vec<&'arena NodeStmt<'arena>>
 }
 
+// `else_stmt` is the scope of an `else`, or the if statement of an `else if`
+pub struct NodeStmtIf<'arena> {
+    pub condition: &'arena NodeExpr<'arena>,
+    pub body: NodeStmtScope<'arena>,
+    pub else_stmt: Option<&'arena NodeStmt<'arena>>,
+}
+
+pub struct NodeStmtWhile<'arena> {
+    pub condition: &'arena NodeExpr<'arena>,
+    pub body: NodeStmtScope<'arena>,
+}
+
+// `for ident in start..end step step { body }`, with `..=` setting `inclusive`. Without a step
+// the counter goes up by one.
+pub struct NodeStmtFor<'arena> {
+    pub ident: Token<'arena>,
+    pub start: &'arena NodeExpr<'arena>,
+    pub end: &'arena NodeExpr<'arena>,
+    pub inclusive: bool,
+    pub step: Option<&'arena NodeExpr<'arena>>,
+    pub body: NodeStmtScope<'arena>,
+}
+
+// `fn ident(params) { body }`, only allowed at the top level
+pub struct NodeStmtFn<'arena> {
+    pub ident: Token<'arena>,
+    pub params: Vec<Token<'arena>>,
+    pub body: NodeStmtScope<'arena>,
+}
+
+// `ident(args);`, a call whose result is discarded
+pub struct NodeStmtCall<'arena> {
+    pub call: &'arena NodeExprCall<'arena>,
+}
+
+// `break` and `continue` keep their keyword token so that misplaced ones can be reported
+pub struct NodeStmtBreak<'arena> {
+    pub keyword: Token<'arena>,
+}
+
+pub struct NodeStmtContinue<'arena> {
+    pub keyword: Token<'arena>,
+}
+
+// Stands in for a statement that failed to parse, so the rest of the program can still be used
+pub struct NodeStmtError {
+    pub error: SyntaxError,
+}
+
 pub enum StmtVariant<'arena> {
     VariantOne(NodeStmtReturn<'arena>),
     VariantTwo(NodeStmtLet<'arena>),  
-    VariantThree(NodeStmtScope<'arena>)
+    VariantThree(NodeStmtScope<'arena>),
+    VariantFour(NodeStmtError),
+    VariantFive(NodeStmtIf<'arena>),
+    VariantSix(NodeStmtWhile<'arena>),
+    VariantSeven(NodeStmtBreak<'arena>),
+    VariantEight(NodeStmtContinue<'arena>),
+    VariantNine(NodeStmtFor<'arena>),
+    VariantTen(NodeStmtAssign<'arena>),
+    VariantEleven(NodeStmtFn<'arena>),
+    VariantTwelve(NodeStmtCall<'arena>),
 }
 
 pub enum NodeBinExprVariant<'arena> {
     VariantOne(NodeBinExprAdd<'arena>),
     VariantTwo(NodeBinExprMul<'arena>),
     VariantThree(NodeBinExprSub<'arena>),
-    VariantFour(NodeBinExprDiv<'arena>)
+    VariantFour(NodeBinExprDiv<'arena>),
+    VariantFive(NodeBinExprEq<'arena>),
+    VariantSix(NodeBinExprNotEq<'arena>),
+    VariantSeven(NodeBinExprLt<'arena>),
+    VariantEight(NodeBinExprLtEq<'arena>),
+    VariantNine(NodeBinExprGt<'arena>),
+    VariantTen(NodeBinExprGtEq<'arena>),
+    VariantEleven(NodeBinExprAnd<'arena>),
+    VariantTwelve(NodeBinExprOr<'arena>),
+    VariantThirteen(NodeBinExprPow<'arena>),
+    VariantFourteen(NodeBinExprMod<'arena>),
 }
 
 pub struct NodeBinExprAdd<'arena> {
@@ -74,6 +213,56 @@ pub struct NodeBinExprDiv<'arena> {
     pub rhs: &'arena NodeExpr<'arena>
 }
 
+pub struct NodeBinExprEq<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
+pub struct NodeBinExprNotEq<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
+pub struct NodeBinExprLt<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
+pub struct NodeBinExprLtEq<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
+pub struct NodeBinExprGt<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
+pub struct NodeBinExprGtEq<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
+pub struct NodeBinExprAnd<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
+pub struct NodeBinExprOr<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
+pub struct NodeBinExprPow<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
+pub struct NodeBinExprMod<'arena> {
+    pub lhs: &'arena NodeExpr<'arena>,
+    pub rhs: &'arena NodeExpr<'arena>
+}
+
 pub struct NodeBinExpr<'arena> {
     pub variant: NodeBinExprVariant<'arena>
 }
@@ -97,6 +286,10 @@ impl<'arena> AstArena<'arena> {
             stmt_arena: Arena::new(),
             bin_expr_arena: Arena::new(),
             paren_expr_arena: Arena::new(),
+            unary_expr_arena: Arena::new(),
+            call_expr_arena: Arena::new(),
+            array_expr_arena: Arena::new(),
+            index_expr_arena: Arena::new(),
         }
     }
 
@@ -111,6 +304,12 @@ impl<'arena> AstArena<'arena> {
    
 }
 
+impl<'arena> Default for AstArena<'arena> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub enum ParseError {
     StatementError,
    
@@ -1,114 +1,251 @@
-use std::thread::Scope;
-
-use crate::fikra_entities::{Precedence, Token, TokenType};
-use super::node::{ AstArena, ExprVar,NodeStmtScope, NodeBinExpr, NodeBinExprAdd,NodeExprParen, NodeBinExprDiv, NodeBinExprMul, NodeBinExprSub, NodeBinExprVariant, NodeExpr, NodeExprIdent, NodeExprIntLit, NodeProg, NodeStmt, NodeStmtLet, NodeStmtReturn, StmtVariant};
-use crate::fikra_errors::{ParseError};
+use crate::fikra_entities::{Precedence, Token, TokenType, TokenValue};
+use crate::fikra_symbols::Symbol;
+use crate::fikra_tokenization::tokenization::tokens::TokenStream;
+use super::node::{ AstArena, ExprVar,NodeStmtScope, NodeBinExpr, NodeBinExprAdd,NodeExprParen, NodeBinExprDiv, NodeBinExprMul, NodeBinExprSub, NodeBinExprEq, NodeBinExprNotEq, NodeBinExprLt, NodeBinExprLtEq, NodeBinExprGt, NodeBinExprGtEq, NodeBinExprAnd, NodeBinExprOr, NodeBinExprPow, NodeBinExprMod, NodeBinExprVariant, NodeExpr, NodeExprIdent, NodeExprIntLit, NodeUnaryExpr, NodeUnaryExprNeg, NodeUnaryExprNot, NodeUnaryExprVariant, NodeProg, NodeStmt, NodeStmtLet, NodeStmtReturn, StmtVariant, NodeStmtError, NodeStmtIf, NodeStmtWhile, NodeStmtBreak, NodeStmtContinue, NodeStmtFor, NodeStmtAssign, NodeStmtFn, NodeStmtCall, NodeExprCall, NodeExprArray, NodeExprArrayVariant, NodeExprArrayList, NodeExprArrayRepeat, NodeExprIndex};
+use crate::fikra_errors::{Construct, Expected, LexError, ParseError, SyntaxError};
 
+// Tokens are pulled from the lexer as the parser needs them rather than lexed up front
 pub struct Parser<'a, 'arena> {
-    tokens: &'a [Token],
+    source: &'a str,
     ast_arena: &'arena AstArena<'arena>,
 }
 
-impl<'a, 'arena> Parser<'a, 'arena> {
-    pub fn new(tokens: &'a [Token],ast_arena: &'arena AstArena<'arena>) -> Self {
-        Parser { tokens, ast_arena }
+// A program parsed with error recovery, along with every error found in it
+pub struct PartialProg<'arena> {
+    pub prog: NodeProg<'arena>,
+    pub syntax_errors: Vec<SyntaxError>,
+    pub lex_errors: Vec<LexError>,
+}
+
+impl<'a: 'arena, 'arena> Parser<'a, 'arena> {
+    pub fn new(source: &'a str,ast_arena: &'arena AstArena<'arena>) -> Self {
+        Parser { source, ast_arena }
     }
 
 
     pub fn parse_prog(&mut self) -> Result<NodeProg<'arena>, ParseError> {
-        let mut parse_tokens = self.tokens.iter().peekable();
+        let partial = self.parse_prog_partial();
+
+        // Lexical errors take priority, since they usually cause the syntax errors
+        if !partial.lex_errors.is_empty() {
+            return Err(ParseError::Lexical(partial.lex_errors));
+        }
+        if !partial.syntax_errors.is_empty() {
+            return Err(ParseError::Syntax(partial.syntax_errors));
+        }
+        Ok(partial.prog)
+    }
+
+    // Parses the whole file even when it has errors. A statement that fails to parse becomes an
+    // error node, and parsing picks up again at the next `;`, `}` or statement keyword.
+    pub fn parse_prog_partial(&mut self) -> PartialProg<'arena> {
+        let mut parse_tokens = TokenStream::new(self.source);
         let mut statements: Vec<&'arena NodeStmt<'arena>> = Vec::new();
+        let mut syntax_errors = Vec::new();
 
-        while let Some(_token) = parse_tokens.peek() {
-            match self.parse_stmt(&mut parse_tokens) {
-                Some(statement) => {
-                    statements.push(statement);
-                },
-                None => return Err(ParseError::InvalidStatement),
+        while parse_tokens.peek().is_some() {
+            statements.push(self.parse_stmt_recovering(&mut parse_tokens, Construct::Program, &mut syntax_errors));
+        }
+
+        PartialProg {
+            prog: NodeProg { statements },
+            syntax_errors,
+            lex_errors: parse_tokens.finish(),
+        }
+    }
+
+    fn parse_stmt_recovering(&self, parse_tokens: &mut TokenStream<'a>, construct: Construct, errors: &mut Vec<SyntaxError>) -> &'arena NodeStmt<'arena> {
+        let start = parse_tokens.position();
+        match self.parse_stmt(parse_tokens, construct, errors) {
+            Ok(statement) => statement,
+            Err(error) => {
+                // Always skip at least one token, or a statement that cannot start would never be passed
+                if parse_tokens.position() == start {
+                    parse_tokens.next();
+                }
+                self.synchronize(parse_tokens, construct);
+                errors.push(error.clone());
+                self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantFour(NodeStmtError { error })
+                })
             }
         }
+    }
 
-        Ok(NodeProg { statements })
+    // Skips to where the next statement is likely to start: past a `;`, or before a `}` closing
+    // the enclosing scope or a statement keyword. A `}` with no scope to close is skipped too.
+    fn synchronize(&self, parse_tokens: &mut TokenStream<'a>, construct: Construct) {
+        while let Some(token) = parse_tokens.peek() {
+            match token._type {
+                TokenType::Semi => {
+                    parse_tokens.next();
+                    return;
+                }
+                TokenType::CloseBrace if construct == Construct::Program => {
+                    parse_tokens.next();
+                    return;
+                }
+                TokenType::CloseBrace => return,
+                _type if _type.is_statement_keyword() => return,
+                _ => {
+                    parse_tokens.next();
+                }
+            }
+        }
     }
 
+    // Error for the next token, which is not consumed
+    fn unexpected(&self, parse_tokens: &mut TokenStream<'a>, expected: Vec<Expected>, construct: Construct) -> SyntaxError {
+        let (line, column) = parse_tokens.position();
+        SyntaxError {
+            expected,
+            found: parse_tokens.peek().map(|token| token._type),
+            construct,
+            line,
+            column,
+        }
+    }
 
+    // Consumes the next token if it has the expected type
+    fn expect(&self, parse_tokens: &mut TokenStream<'a>, _type: TokenType, construct: Construct) -> Result<Token<'a>, SyntaxError> {
+        match parse_tokens.peek() {
+            Some(token) if token._type == _type => Ok(parse_tokens.next().unwrap()),
+            _ => Err(self.unexpected(parse_tokens, vec![Expected::Token(_type)], construct)),
+        }
+    }
     
-    fn parse_expr(&self, parse_tokens: &mut std::iter::Peekable<std::slice::Iter<'a, Token>>) -> Option<&'arena NodeExpr<'arena>> {
-        self.parse_expr_with_precedence(parse_tokens, Precedence::Lowest)
+    fn parse_expr(&self, parse_tokens: &mut TokenStream<'a>, construct: Construct) -> Result<&'arena NodeExpr<'arena>, SyntaxError> {
+        self.parse_expr_with_precedence(parse_tokens, Precedence::Lowest, construct)
     }
     
-    fn parse_primary(&self, parse_tokens: &mut std::iter::Peekable<std::slice::Iter<'a, Token>>) -> Option<&'arena NodeExpr<'arena>> {
-        if let Some(&token) = parse_tokens.peek() {
-            let expr = match token._type {
-                TokenType::Int32Lit => {
-                    parse_tokens.next(); 
-                    NodeExpr {
-                    
-                    variant: ExprVar::VariantOne(NodeExprIntLit { int_lit: token.clone() })
-                    }
-               },
-                TokenType::Ident => {
-                    parse_tokens.next(); 
-                    NodeExpr {
-                        variant: ExprVar::VariantTwo(NodeExprIdent { ident: token.clone() })
-                    }
-                },
-                TokenType::OpenParen =>{ 
-                    parse_tokens.next();
-                    let inner_expr = self.parse_expr(parse_tokens)?;
-                    if let Some(close_paren) = parse_tokens.next() {
-                        if close_paren._type != TokenType::CloseParen {
-                            return None; // Mismatched parentheses
+    fn parse_primary(&self, parse_tokens: &mut TokenStream<'a>, construct: Construct) -> Result<&'arena NodeExpr<'arena>, SyntaxError> {
+        let _type = match parse_tokens.peek() {
+            Some(token) => token._type,
+            None => return Err(self.unexpected(parse_tokens, vec![Expected::Expression], construct)),
+        };
+        let expr = match _type {
+            _type if _type.is_int_lit() || matches!(_type, TokenType::CharLit | TokenType::BoolLit) => {
+                let token = parse_tokens.next().unwrap();
+                NodeExpr {
+                
+                variant: ExprVar::VariantOne(NodeExprIntLit { int_lit: token })
+                }
+           },
+            TokenType::Ident => {
+                let token = parse_tokens.next().unwrap();
+                match parse_tokens.peek() {
+                    Some(next) if next._type == TokenType::OpenParen => NodeExpr {
+                        variant: ExprVar::VariantSix(self.parse_call(token, parse_tokens)?)
+                    },
+                    Some(next) if next._type == TokenType::OpenBracket => {
+                        parse_tokens.next(); // Consume the OpenBracket token
+                        let index = self.parse_expr(parse_tokens, Construct::IndexExpression)?;
+                        self.expect(parse_tokens, TokenType::CloseBracket, Construct::IndexExpression)?;
+                        NodeExpr {
+                            variant: ExprVar::VariantEight(self.ast_arena.index_expr_arena.alloc(NodeExprIndex { ident: token, index }))
+                        }
+                    },
+                    _ => NodeExpr {
+                        variant: ExprVar::VariantTwo(NodeExprIdent { ident: token })
+                    },
+                }
+            },
+            TokenType::OpenParen =>{ 
+                parse_tokens.next(); // Consume the OpenParen token
+                let inner_expr = self.parse_expr(parse_tokens, Construct::ParenExpression)?;
+                self.expect(parse_tokens, TokenType::CloseParen, Construct::ParenExpression)?;
+                let paren_expr = NodeExprParen{expr: inner_expr};
+                let paren_expr_ref = self.ast_arena.paren_expr_arena.alloc(paren_expr);
+                NodeExpr {
+                    variant: ExprVar::VariantFour(paren_expr_ref)
+                }
+            }
+            TokenType::OpenBracket => {
+                let open_bracket = parse_tokens.next().unwrap();
+                let is_empty = parse_tokens.peek().is_some_and(|token| token._type == TokenType::CloseBracket);
+                let variant = if is_empty {
+                    parse_tokens.next(); // Consume the CloseBracket token
+                    NodeExprArrayVariant::VariantOne(NodeExprArrayList { elements: Vec::new() })
+                } else {
+                    let first = self.parse_expr(parse_tokens, Construct::ArrayLiteral)?;
+                    match parse_tokens.peek() {
+                        Some(token) if token._type == TokenType::Semi => {
+                            parse_tokens.next(); // Consume the Semi token
+                            let count = self.parse_expr(parse_tokens, Construct::ArrayLiteral)?;
+                            self.expect(parse_tokens, TokenType::CloseBracket, Construct::ArrayLiteral)?;
+                            NodeExprArrayVariant::VariantTwo(NodeExprArrayRepeat { value: first, count })
+                        },
+                        Some(token) if token._type == TokenType::CloseBracket => {
+                            parse_tokens.next(); // Consume the CloseBracket token
+                            NodeExprArrayVariant::VariantOne(NodeExprArrayList { elements: vec![first] })
+                        },
+                        Some(token) if token._type == TokenType::Comma => {
+                            parse_tokens.next(); // Consume the Comma token
+                            let mut elements = vec![first];
+                            elements.extend(self.parse_list(parse_tokens, TokenType::CloseBracket, Construct::ArrayLiteral, |parser, parse_tokens| {
+                                parser.parse_expr(parse_tokens, Construct::ArrayLiteral)
+                            })?);
+                            NodeExprArrayVariant::VariantOne(NodeExprArrayList { elements })
+                        },
+                        _ => {
+                            let expected = [TokenType::Comma, TokenType::Semi, TokenType::CloseBracket];
+                            return Err(self.unexpected(parse_tokens, expected.into_iter().map(Expected::Token).collect(), Construct::ArrayLiteral));
                         }
-                    }else{
-                        return None; // Missing closing parenthesis
-                    }
-                    let paren_expr = NodeExprParen{expr: inner_expr};
-                    let paren_expr_ref = self.ast_arena.paren_expr_arena.alloc(paren_expr);
-                    NodeExpr {
-                        variant: ExprVar::VariantFour(paren_expr_ref)
                     }
+                };
+                NodeExpr {
+                    variant: ExprVar::VariantSeven(self.ast_arena.array_expr_arena.alloc(NodeExprArray { open_bracket, variant }))
                 }
-                _ =>{
-                    
-                    return  None
+            },
+            TokenType::Minus | TokenType::Not => {
+                parse_tokens.next(); // Consume the operator token
+                // Binds tighter than every binary operator, so `-x ^ 2` is `(-x) ^ 2`
+                let operand = self.parse_expr_with_precedence(parse_tokens, Precedence::Unary, construct)?;
+                let variant = if _type == TokenType::Minus {
+                    NodeUnaryExprVariant::VariantOne(NodeUnaryExprNeg { expr: operand })
+                } else {
+                    NodeUnaryExprVariant::VariantTwo(NodeUnaryExprNot { expr: operand })
+                };
+                NodeExpr {
+                    variant: ExprVar::VariantFive(self.ast_arena.unary_expr_arena.alloc(NodeUnaryExpr { variant }))
                 }
-            };
-            Some(self.ast_arena.expr_arena.alloc(expr))
-        } else {
-            
-            None
-            
-        }
+            }
+            _ =>{
+                
+                return Err(self.unexpected(parse_tokens, vec![Expected::Expression], construct))
+            }
+        };
+        Ok(self.ast_arena.expr_arena.alloc(expr))
     }
 
-    fn parse_expr_with_precedence(&self, parse_tokens: &mut std::iter::Peekable<std::slice::Iter<'a, Token>>, min_precedence: Precedence) -> Option<&'arena NodeExpr<'arena>> {
-        let mut left: &NodeExpr<'arena> = self.parse_primary(parse_tokens)?;
+    fn parse_expr_with_precedence(&self, parse_tokens: &mut TokenStream<'a>, min_precedence: Precedence, construct: Construct) -> Result<&'arena NodeExpr<'arena>, SyntaxError> {
+        let mut left: &NodeExpr<'arena> = self.parse_primary(parse_tokens, construct)?;
         
-        while let Some(&token) = parse_tokens.peek() {
+        while let Some(token) = parse_tokens.peek() {
             
             let token_precedence = token._type.get_precedence();
 
-           // Break if it's not an operator or if its precedence is too low
-            if token_precedence == Precedence::Lowest || token_precedence < min_precedence {
+           // Break if it's not a binary operator or if its precedence is too low
+            if !token._type.is_binary_op() || token_precedence < min_precedence {
                 break;
             }
             
-            parse_tokens.next(); // Consume the operator token
+            let token = parse_tokens.next().unwrap(); // Consume the operator token
     
-            // Parse the right side with higher precedence
-            let right = self.parse_expr_with_precedence(parse_tokens, token_precedence.next_higher())?;
+            // Parse the right side with higher precedence, or the same one if the operator groups to the right
+            let right_precedence = if token._type.is_right_associative() { token_precedence } else { token_precedence.next_higher() };
+            let right = self.parse_expr_with_precedence(parse_tokens, right_precedence, construct)?;
     
             // parse a new binary expression node
-            left = self.parse_binary_expr(left, token, right)?;
+            left = self.parse_binary_expr(left, &token, right);
             
     
         }
     
-        Some(left)
+        Ok(left)
     }
     
-    fn parse_binary_expr(&self, left: &'arena NodeExpr<'arena>, op: &Token, right: &'arena NodeExpr<'arena>) -> Option<&'arena NodeExpr<'arena>> {
+    fn parse_binary_expr(&self, left: &'arena NodeExpr<'arena>, op: &Token, right: &'arena NodeExpr<'arena>) -> &'arena NodeExpr<'arena> {
         let bin_expr = match op._type {
             TokenType::Plus =>  NodeBinExpr {   
                 variant: NodeBinExprVariant::VariantOne(NodeBinExprAdd { lhs: left, rhs: right })
@@ -122,100 +259,322 @@ impl<'a, 'arena> Parser<'a, 'arena> {
             TokenType::Slash => NodeBinExpr{
                 variant: NodeBinExprVariant::VariantFour(NodeBinExprDiv{lhs: left, rhs: right})
             },
-            // Add other binary operators here...
-            _ => return Some(left), // Not a binary operator, return left as is
+            TokenType::EqEq => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantFive(NodeBinExprEq{lhs: left, rhs: right})
+            },
+            TokenType::NotEq => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantSix(NodeBinExprNotEq{lhs: left, rhs: right})
+            },
+            TokenType::Lt => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantSeven(NodeBinExprLt{lhs: left, rhs: right})
+            },
+            TokenType::LtEq => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantEight(NodeBinExprLtEq{lhs: left, rhs: right})
+            },
+            TokenType::Gt => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantNine(NodeBinExprGt{lhs: left, rhs: right})
+            },
+            TokenType::GtEq => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantTen(NodeBinExprGtEq{lhs: left, rhs: right})
+            },
+            TokenType::And => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantEleven(NodeBinExprAnd{lhs: left, rhs: right})
+            },
+            TokenType::Or => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantTwelve(NodeBinExprOr{lhs: left, rhs: right})
+            },
+            TokenType::Caret => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantThirteen(NodeBinExprPow{lhs: left, rhs: right})
+            },
+            TokenType::Percent => NodeBinExpr{
+                variant: NodeBinExprVariant::VariantFourteen(NodeBinExprMod{lhs: left, rhs: right})
+            },
+            // Only called for tokens that is_binary_op accepts
+            _ => unreachable!("{:?} is not a binary operator", op._type),
         };
     
         let expr = NodeExpr {
             variant: ExprVar::VariantThree(self.ast_arena.bin_expr_arena.alloc(bin_expr))
         };
-        Some(self.ast_arena.expr_arena.alloc(expr))
+        self.ast_arena.expr_arena.alloc(expr)
     }
 
-    fn parse_stmt(&self, parse_tokens: &mut std::iter::Peekable<std::slice::Iter<'a, Token>>) -> Option<&'arena NodeStmt<'arena>> {
-        if let Some(&token) = parse_tokens.peek() {
-            match token._type {
-                TokenType::Return => {
-                    parse_tokens.next(); // Consume the Return token
+    // `construct` is the construct the statement appears in, for errors about its first token
+    fn parse_stmt(&self, parse_tokens: &mut TokenStream<'a>, construct: Construct, errors: &mut Vec<SyntaxError>) -> Result<&'arena NodeStmt<'arena>, SyntaxError> {
+        let _type = match parse_tokens.peek() {
+            Some(token) => token._type,
+            None => return Err(self.unexpected(parse_tokens, vec![Expected::Statement], construct)),
+        };
+        match _type {
+            TokenType::Return => {
+                parse_tokens.next(); // Consume the Return token
 
-                    let expr = self.parse_expr(parse_tokens)?;
+                let expr = self.parse_expr(parse_tokens, Construct::ReturnStatement)?;
 
-                    // Check for semicolon
-                    if parse_tokens.next()?._type != TokenType::Semi {
-                        
-                        return None;
-                    }
+                // Check for semicolon
+                self.expect(parse_tokens, TokenType::Semi, Construct::ReturnStatement)?;
 
-                    let return_stmt = NodeStmtReturn { expr };
-                    Some(self.ast_arena.stmt_arena.alloc(NodeStmt {
-                        variant: StmtVariant::VariantOne(return_stmt)
-                    }))
-                },
-                TokenType::Let => {
-                    parse_tokens.next(); // Consume the Let token
+                let return_stmt = NodeStmtReturn { expr };
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantOne(return_stmt)
+                }))
+            },
+            TokenType::Let => {
+                parse_tokens.next(); // Consume the Let token
 
-                    // Check for identifier
-                    let ident_token = parse_tokens.next()?;
-                    if ident_token._type != TokenType::Ident {
-                        return None;
-                    }
+                let mutable = match parse_tokens.peek() {
+                    Some(token) if token._type == TokenType::Mut => {
+                        parse_tokens.next(); // Consume the Mut token
+                        true
+                    },
+                    _ => false,
+                };
 
-                    // Check for '=' sign
-                    if parse_tokens.next()?._type != TokenType::Eq {
-                        return None;
-                    }
+                // Check for identifier
+                let ident_token = self.expect(parse_tokens, TokenType::Ident, Construct::LetStatement)?;
 
+                // Check for '=' sign
+                self.expect(parse_tokens, TokenType::Eq, Construct::LetStatement)?;
 
-                    let expr = self.parse_expr(parse_tokens)?;
+                let expr = self.parse_expr(parse_tokens, Construct::LetStatement)?;
 
-                    // Check for semicolon
-                    if parse_tokens.next()?._type != TokenType::Semi {
-                        return None;
-                    }
+                // Check for semicolon
+                self.expect(parse_tokens, TokenType::Semi, Construct::LetStatement)?;
 
-                    let let_stmt = NodeStmtLet {
-                        ident: ident_token.clone(),
-                        expr
-                    };
+                let let_stmt = NodeStmtLet {
+                    ident: ident_token,
+                    expr,
+                    mutable
+                };
 
 
-                    Some(self.ast_arena.stmt_arena.alloc(NodeStmt {
-                        variant: StmtVariant::VariantTwo(let_stmt)
-                    }))
-                },
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantTwo(let_stmt)
+                }))
+            },
 
-                // Suggested Improved Implementation
-                TokenType::OpenBrace => {
-                    parse_tokens.next(); // Consume the OpenBrace token
-                    let mut scope_statements: Vec<&'arena NodeStmt<'arena>> = Vec::new();
-                    
-                    while let Some(token) = parse_tokens.peek() {
-                        if token._type == TokenType::CloseBrace {
-                            break;
-                        }
-                        match self.parse_stmt(parse_tokens) {
-                            Some(stmt) => scope_statements.push(stmt),
-                            None => return None // Early return on parsing error
+            TokenType::Ident => {
+                let ident = parse_tokens.next().unwrap();
+
+                // A call made for its side effects; the value it returns is dropped
+                if parse_tokens.peek().is_some_and(|token| token._type == TokenType::OpenParen) {
+                    let call = self.parse_call(ident, parse_tokens)?;
+                    self.expect(parse_tokens, TokenType::Semi, Construct::CallStatement)?;
+                    return Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                        variant: StmtVariant::VariantTwelve(NodeStmtCall { call })
+                    }));
+                }
+
+                let index = match parse_tokens.peek() {
+                    Some(token) if token._type == TokenType::OpenBracket => {
+                        parse_tokens.next(); // Consume the OpenBracket token
+                        let index = self.parse_expr(parse_tokens, Construct::IndexExpression)?;
+                        self.expect(parse_tokens, TokenType::CloseBracket, Construct::IndexExpression)?;
+                        Some(index)
+                    },
+                    _ => None,
+                };
+
+                let op = match parse_tokens.peek() {
+                    Some(token) if token._type == TokenType::Eq || token._type.compound_assignment_op().is_some() => {
+                        parse_tokens.next().unwrap()
+                    },
+                    _ => {
+                        let mut expected = vec![TokenType::Eq, TokenType::PlusEq, TokenType::MinusEq, TokenType::StarEq, TokenType::SlashEq, TokenType::PercentEq];
+                        if index.is_none() {
+                            expected.push(TokenType::OpenParen);
                         }
+                        return Err(self.unexpected(parse_tokens, expected.into_iter().map(Expected::Token).collect(), Construct::AssignStatement));
                     }
-                    
-                    match parse_tokens.next() {
-                        Some(token) if token._type == TokenType::CloseBrace => {
-                            let node_scope_stmts = NodeStmtScope {
-                                statements: scope_statements
-                            };
-                            Some(self.ast_arena.stmt_arena.alloc(NodeStmt {
-                                variant: StmtVariant::VariantThree(node_scope_stmts)
-                            }))
-                        },
-                        _ => None
+                };
+
+                let mut expr = self.parse_expr(parse_tokens, Construct::AssignStatement)?;
+                let compound_op = match (op._type.compound_assignment_op(), index) {
+                    (Some(bin_op), None) => {
+                        let current = self.ast_arena.new_expr(ExprVar::VariantTwo(NodeExprIdent { ident: ident.clone() }));
+                        expr = self.parse_binary_expr(current, &Token { _type: bin_op, ..op }, expr);
+                        None
+                    },
+                    (compound_op, _) => compound_op,
+                };
+
+                self.expect(parse_tokens, TokenType::Semi, Construct::AssignStatement)?;
+
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantTen(NodeStmtAssign { ident, index, op: compound_op, expr })
+                }))
+            },
+            TokenType::OpenBrace => {
+                let node_scope_stmts = self.parse_scope(parse_tokens, Construct::Scope, errors)?;
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantThree(node_scope_stmts)
+                }))
+            },
+            TokenType::If => {
+                parse_tokens.next(); // Consume the If token
+
+                let condition = self.parse_expr(parse_tokens, Construct::IfStatement)?;
+                let body = self.parse_scope(parse_tokens, Construct::IfStatement, errors)?;
+
+                let else_stmt = match parse_tokens.peek() {
+                    Some(token) if token._type == TokenType::Else => {
+                        parse_tokens.next(); // Consume the Else token
+                        match parse_tokens.peek() {
+                            // `else if` chains nest the next if statement as the else branch
+                            Some(token) if token._type == TokenType::If => Some(self.parse_stmt(parse_tokens, Construct::IfStatement, errors)?),
+                            _ => {
+                                let else_scope = self.parse_scope(parse_tokens, Construct::IfStatement, errors)?;
+                                Some(&*self.ast_arena.stmt_arena.alloc(NodeStmt {
+                                    variant: StmtVariant::VariantThree(else_scope)
+                                }))
+                            }
+                        }
+                    },
+                    _ => None,
+                };
+
+                let if_stmt = NodeStmtIf { condition, body, else_stmt };
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantFive(if_stmt)
+                }))
+            },
+            TokenType::While => {
+                parse_tokens.next(); // Consume the While token
+
+                let condition = self.parse_expr(parse_tokens, Construct::WhileStatement)?;
+                let body = self.parse_scope(parse_tokens, Construct::WhileStatement, errors)?;
+
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantSix(NodeStmtWhile { condition, body })
+                }))
+            },
+            TokenType::For => {
+                parse_tokens.next(); // Consume the For token
+
+                let ident = self.expect(parse_tokens, TokenType::Ident, Construct::ForStatement)?;
+                self.expect(parse_tokens, TokenType::In, Construct::ForStatement)?;
+
+                let start = self.parse_expr(parse_tokens, Construct::ForStatement)?;
+                let inclusive = match parse_tokens.peek() {
+                    Some(token) if token._type == TokenType::DotDot => false,
+                    Some(token) if token._type == TokenType::DotDotEq => true,
+                    _ => {
+                        let expected = vec![Expected::Token(TokenType::DotDot), Expected::Token(TokenType::DotDotEq)];
+                        return Err(self.unexpected(parse_tokens, expected, Construct::ForStatement));
                     }
+                };
+                parse_tokens.next(); // Consume the range operator
+                let end = self.parse_expr(parse_tokens, Construct::ForStatement)?;
+
+                // `step` is only a keyword here, so it stays usable as a name elsewhere
+                let step = match parse_tokens.peek() {
+                    Some(token) if token._type == TokenType::Ident && token.value == Some(TokenValue::Identifier(Symbol::intern("step"))) => {
+                        parse_tokens.next(); // Consume `step`
+                        Some(self.parse_expr(parse_tokens, Construct::ForStatement)?)
+                    },
+                    _ => None,
+                };
+
+                let body = self.parse_scope(parse_tokens, Construct::ForStatement, errors)?;
+
+                let for_stmt = NodeStmtFor { ident, start, end, inclusive, step, body };
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantNine(for_stmt)
+                }))
+            },
+            // Functions can only be declared at the top level
+            TokenType::Fn if construct == Construct::Program => {
+                parse_tokens.next(); // Consume the Fn token
+
+                let ident = self.expect(parse_tokens, TokenType::Ident, Construct::FunctionDeclaration)?;
+                self.expect(parse_tokens, TokenType::OpenParen, Construct::FunctionDeclaration)?;
+                let params = self.parse_list(parse_tokens, TokenType::CloseParen, Construct::FunctionDeclaration, |parser, parse_tokens| {
+                    parser.expect(parse_tokens, TokenType::Ident, Construct::FunctionDeclaration)
+                })?;
+                let body = self.parse_scope(parse_tokens, Construct::FunctionDeclaration, errors)?;
+
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantEleven(NodeStmtFn { ident, params, body })
+                }))
+            },
+            TokenType::Break => {
+                let keyword = parse_tokens.next().unwrap();
+                self.expect(parse_tokens, TokenType::Semi, Construct::BreakStatement)?;
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantSeven(NodeStmtBreak { keyword })
+                }))
+            },
+            TokenType::Continue => {
+                let keyword = parse_tokens.next().unwrap();
+                self.expect(parse_tokens, TokenType::Semi, Construct::ContinueStatement)?;
+                Ok(self.ast_arena.stmt_arena.alloc(NodeStmt {
+                    variant: StmtVariant::VariantEight(NodeStmtContinue { keyword })
+                }))
+            },
+            _ => Err(self.unexpected(parse_tokens, vec![Expected::Statement], construct))
+        }
+    }
+
+    // A `{ ... }` block. `construct` is the construct it is the body of, for a missing `{`.
+    fn parse_scope(&self, parse_tokens: &mut TokenStream<'a>, construct: Construct, errors: &mut Vec<SyntaxError>) -> Result<NodeStmtScope<'arena>, SyntaxError> {
+        self.expect(parse_tokens, TokenType::OpenBrace, construct)?;
+        let mut scope_statements: Vec<&'arena NodeStmt<'arena>> = Vec::new();
+        
+        loop {
+            match parse_tokens.peek() {
+                Some(token) if token._type == TokenType::CloseBrace => break,
+                Some(_) => scope_statements.push(self.parse_stmt_recovering(parse_tokens, Construct::Scope, errors)),
+                None => {
+                    // Keep the statements of an unclosed scope rather than dropping them
+                    errors.push(self.unexpected(parse_tokens, vec![Expected::Statement, Expected::Token(TokenType::CloseBrace)], Construct::Scope));
+                    break;
+                }
+            }
+        }
+        parse_tokens.next(); // Consume the CloseBrace token
+
+        Ok(NodeStmtScope {
+            statements: scope_statements
+        })
+    }
+
+    // Comma-separated items up to and including the `close` token, the opening one having been consumed
+    // Called with the `(` after a function name next; parses the arguments up to the `)`
+    fn parse_call(&self, ident: Token<'a>, parse_tokens: &mut TokenStream<'a>) -> Result<&'arena NodeExprCall<'arena>, SyntaxError> {
+        parse_tokens.next(); // Consume the OpenParen token
+        let args = self.parse_list(parse_tokens, TokenType::CloseParen, Construct::CallExpression, |parser, parse_tokens| {
+            parser.parse_expr(parse_tokens, Construct::CallExpression)
+        })?;
+        Ok(self.ast_arena.call_expr_arena.alloc(NodeExprCall { ident, args }))
+    }
+
+    fn parse_list<T>(
+        &self,
+        parse_tokens: &mut TokenStream<'a>,
+        close: TokenType,
+        construct: Construct,
+        parse_item: impl Fn(&Self, &mut TokenStream<'a>) -> Result<T, SyntaxError>,
+    ) -> Result<Vec<T>, SyntaxError> {
+        let mut items = Vec::new();
+        if parse_tokens.peek().is_some_and(|token| token._type == close) {
+            parse_tokens.next();
+            return Ok(items);
+        }
+        loop {
+            items.push(parse_item(self, parse_tokens)?);
+            match parse_tokens.peek() {
+                Some(token) if token._type == TokenType::Comma => {
+                    parse_tokens.next();
                 },
-                _ => None
+                Some(token) if token._type == close => {
+                    parse_tokens.next();
+                    return Ok(items);
+                },
+                _ => {
+                    let expected = vec![Expected::Token(TokenType::Comma), Expected::Token(close)];
+                    return Err(self.unexpected(parse_tokens, expected, construct));
+                }
             }
-        } else {
-           
-            None
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,41 @@
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+
+use indexmap::IndexSet;
+
+// An interned identifier. Comparing, hashing and copying a Symbol is as cheap as a u32,
+// and `as_str` recovers the name for diagnostics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+// Global symbol table. Names are leaked so that they can be handed out as &'static str, and
+// none is ever freed. That suits a compiler run, which sees a bounded set of names, but not a
+// long-lived process: an editor calling `relex` on every keystroke would keep each partial
+// identifier typed (`f`, `fo`, `foo`, ...) until it exits.
+fn interner() -> &'static Mutex<IndexSet<&'static str>> {
+    static INTERNER: OnceLock<Mutex<IndexSet<&'static str>>> = OnceLock::new();
+    INTERNER.get_or_init(|| Mutex::new(IndexSet::new()))
+}
+
+impl Symbol {
+    pub fn intern(name: &str) -> Symbol {
+        let mut names = interner().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(index) = names.get_index_of(name) {
+            return Symbol(index as u32);
+        }
+        let name: &'static str = Box::leak(name.to_string().into_boxed_str());
+        let (index, _) = names.insert_full(name);
+        Symbol(index as u32)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        let names = interner().lock().unwrap_or_else(|e| e.into_inner());
+        names.get_index(self.0 as usize).expect("symbol was not created by this interner")
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -1,2 +1,3 @@
 pub mod tokenization;
+pub mod incremental;
 //pub mod to_asm;
\ No newline at end of file
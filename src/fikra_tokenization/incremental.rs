@@ -0,0 +1,216 @@
+pub mod relex {
+    use std::borrow::Cow;
+    use std::ops::Range;
+
+    use crate::fikra_entities::{Token, TokenType, TokenValue, Trivia};
+    use crate::fikra_errors::LexError;
+    use crate::fikra_tokenization::tokenization::tokens::Lexer;
+
+    // The lexer decides where a token ends by looking at most three characters past it
+    // (`1e+5`, `1..n`, `////`), which is at most this many bytes
+    const LOOKAHEAD_BYTES: usize = 3 * 4;
+
+    // Replacement of the bytes in `range` of the old source with `replacement`
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct TextEdit<'e> {
+        pub range: Range<usize>,
+        pub replacement: &'e str,
+    }
+
+    impl TextEdit<'_> {
+        pub fn apply(&self, source: &str) -> String {
+            let mut edited = String::with_capacity(source.len() + self.replacement.len());
+            edited.push_str(&source[..self.range.start]);
+            edited.push_str(self.replacement);
+            edited.push_str(&source[self.range.end..]);
+            edited
+        }
+    }
+
+    pub struct Relexed<'src> {
+        pub tokens: Vec<Token<'src>>,
+        // Lexical errors in the re-lexed region. Errors reported earlier for untouched text still
+        // apply, with their spans shifted like the tokens around them.
+        pub errors: Vec<LexError>,
+        // Indices of the re-lexed tokens in `tokens`
+        pub changed: Range<usize>,
+        // Indices of the tokens in the old vector that `changed` replaces
+        pub replaced: Range<usize>,
+    }
+
+    // Updates `old_tokens`, lexed from `old_source`, for `edit`. `new_source` must be `old_source`
+    // with the edit applied. Lexing restarts just before the edit and stops as soon as it produces
+    // a token identical to an old one past the edit; every token after that is reused with its
+    // span and position shifted. Tokens from `Lexer::lossless` (ending in EOF) stay lossless.
+    // Identifiers are interned in the compiler's global symbol table, which is never emptied.
+    pub fn relex<'new>(old_source: &str, old_tokens: &[Token<'_>], edit: &TextEdit, new_source: &'new str) -> Relexed<'new> {
+        debug_assert_eq!(new_source.len() + edit.range.len(), old_source.len() + edit.replacement.len());
+        let delta = edit.replacement.len() as isize - edit.range.len() as isize;
+        let lossless = old_tokens.last().is_some_and(|token| token._type == TokenType::EOF);
+
+        // A token that ends close enough to the edit for the lexer to have looked at the edited
+        // text may lex differently, so it is re-lexed as well
+        let mut first = old_tokens
+            .iter()
+            .position(|token| full_span(token).end + LOOKAHEAD_BYTES >= edit.range.start)
+            .unwrap_or(old_tokens.len());
+        // In lossless mode the edit can also change the trailing trivia of the token before it
+        if lossless {
+            first = first.saturating_sub(1);
+        }
+        let (offset, line, column, prev) = match first.checked_sub(1).map(|i| &old_tokens[i]) {
+            Some(before) => {
+                let end = full_span(before).end;
+                let (line, column) = advance(before.line, before.column, &old_source[before.span.start..end]);
+                (end, line, column, Some(before._type))
+            }
+            None => (0, 1, 1, None),
+        };
+
+        let mut tokens: Vec<Token<'new>> = old_tokens[..first].iter().map(|token| rebase(token, old_source, new_source, edit, 0, 0)).collect();
+        let mut lexer = Lexer::resume(new_source, offset, line, column, prev, lossless);
+        if let Some(before) = tokens.last() {
+            lexer.carry_doc_comments(&before.trailing_trivia);
+        }
+
+        let edit_end = edit.range.start + edit.replacement.len();
+        let mut relexed = Vec::new();
+        let mut errors = Vec::new();
+        let mut candidate = first;
+        let mut resync = None;
+
+        for item in lexer {
+            let token = match item {
+                Ok(token) => token,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                }
+            };
+
+            if token.span.start >= edit_end {
+                while candidate < old_tokens.len()
+                    && (old_tokens[candidate].span.start < edit.range.end || shift(old_tokens[candidate].span.start, delta) < token.span.start)
+                {
+                    candidate += 1;
+                }
+                if candidate < old_tokens.len() && is_same_token(&old_tokens[candidate], &token, old_source, edit) {
+                    resync = Some((candidate, token.line as isize - old_tokens[candidate].line as isize, token.column as isize - old_tokens[candidate].column as isize));
+                    break;
+                }
+            }
+            relexed.push(token);
+        }
+
+        let replaced_end = resync.map_or(old_tokens.len(), |(index, _, _)| index);
+        let changed = first..first + relexed.len();
+        tokens.extend(relexed);
+        if let Some((index, line_delta, column_delta)) = resync {
+            let resync_line = old_tokens[index].line;
+            for token in &old_tokens[index..] {
+                // Only tokens on the same line as the resync point move sideways
+                let column_delta = if token.line == resync_line { column_delta } else { 0 };
+                tokens.push(rebase(token, old_source, new_source, edit, line_delta, column_delta));
+            }
+        }
+
+        Relexed { tokens, errors, changed, replaced: first..replaced_end }
+    }
+
+    // Span of the token together with its trivia
+    fn full_span(token: &Token) -> Range<usize> {
+        let leading: usize = token.leading_trivia.iter().map(|piece| piece.text.len()).sum();
+        let trailing: usize = token.trailing_trivia.iter().map(|piece| piece.text.len()).sum();
+        token.span.start - leading..token.span.end + trailing
+    }
+
+    // Line and column after `text`, counted the way the lexer counts them
+    fn advance(mut line: usize, mut column: usize, text: &str) -> (usize, usize) {
+        for c in text.chars() {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+
+    fn shift(offset: usize, delta: isize) -> usize {
+        offset.wrapping_add_signed(delta)
+    }
+
+    // Whether `new` is `old` moved by the edit. The old token and its trivia must lie wholly after
+    // the edit, and a doc comment it borrows from before the edit must not overlap it either.
+    fn is_same_token(old: &Token, new: &Token, old_source: &str, edit: &TextEdit) -> bool {
+        let delta = edit.replacement.len() as isize - edit.range.len() as isize;
+        let trivia_text = |trivia: &[Trivia]| trivia.iter().map(|piece| piece.text).collect::<String>();
+        let doc_overlaps_edit = match &old.doc {
+            Some(Cow::Borrowed(text)) => {
+                let start = text.as_ptr() as usize - old_source.as_ptr() as usize;
+                start < edit.range.end && start + text.len() > edit.range.start
+            }
+            _ => false,
+        };
+
+        full_span(old).start >= edit.range.end
+            && !doc_overlaps_edit
+            && old._type == new._type
+            && old.value == new.value
+            && old.doc == new.doc
+            && shift(old.span.start, delta) == new.span.start
+            && old.span.len() == new.span.len()
+            && trivia_text(&old.leading_trivia) == trivia_text(&new.leading_trivia)
+            && trivia_text(&old.trailing_trivia) == trivia_text(&new.trailing_trivia)
+    }
+
+    // Moves a token lexed from `old_source` over to `new_source`, re-pointing borrowed text.
+    // Offsets past the edit shift by its change in length; a reused token's doc comment may
+    // still lie before the edit.
+    fn rebase<'new>(token: &Token, old_source: &str, new_source: &'new str, edit: &TextEdit, line_delta: isize, column_delta: isize) -> Token<'new> {
+        let delta = edit.replacement.len() as isize - edit.range.len() as isize;
+        let moved = |offset: usize| if offset >= edit.range.end { shift(offset, delta) } else { offset };
+        let reborrow = |text: &str| -> &'new str {
+            let start = moved(text.as_ptr() as usize - old_source.as_ptr() as usize);
+            &new_source[start..start + text.len()]
+        };
+        let recow = |text: &Cow<str>| -> Cow<'new, str> {
+            match text {
+                Cow::Borrowed(text) => Cow::Borrowed(reborrow(text)),
+                Cow::Owned(text) => Cow::Owned(text.clone()),
+            }
+        };
+
+        let value = token.value.as_ref().map(|value| match value {
+            TokenValue::Str(text) => TokenValue::Str(recow(text)),
+            TokenValue::Int8(n) => TokenValue::Int8(*n),
+            TokenValue::Int16(n) => TokenValue::Int16(*n),
+            TokenValue::Int32(n) => TokenValue::Int32(*n),
+            TokenValue::Int64(n) => TokenValue::Int64(*n),
+            TokenValue::UInt8(n) => TokenValue::UInt8(*n),
+            TokenValue::UInt16(n) => TokenValue::UInt16(*n),
+            TokenValue::UInt32(n) => TokenValue::UInt32(*n),
+            TokenValue::UInt64(n) => TokenValue::UInt64(*n),
+            TokenValue::Float32(n) => TokenValue::Float32(*n),
+            TokenValue::Float64(n) => TokenValue::Float64(*n),
+            TokenValue::Boolean(b) => TokenValue::Boolean(*b),
+            TokenValue::Identifier(symbol) => TokenValue::Identifier(*symbol),
+            TokenValue::Char(c) => TokenValue::Char(*c),
+        });
+        let trivia = |pieces: &[Trivia]| -> Vec<Trivia<'new>> {
+            pieces.iter().map(|piece| Trivia { kind: piece.kind, text: reborrow(piece.text) }).collect()
+        };
+
+        Token {
+            _type: token._type,
+            value,
+            line: token.line.wrapping_add_signed(line_delta),
+            column: token.column.wrapping_add_signed(column_delta),
+            span: moved(token.span.start)..moved(token.span.end),
+            doc: token.doc.as_ref().map(recow),
+            leading_trivia: trivia(&token.leading_trivia),
+            trailing_trivia: trivia(&token.trailing_trivia),
+        }
+    }
+}
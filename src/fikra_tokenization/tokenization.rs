@@ -1,78 +1,702 @@
 pub mod tokens {
-    use crate::fikra_entities::{Token, TokenType, TokenValue};
+    use std::borrow::Cow;
+    use std::collections::VecDeque;
+    use std::iter::Peekable;
+    use std::str::CharIndices;
 
-    pub fn tokenize(contents: String) -> Vec<Token> {
+    use unicode_normalization::{is_nfc, UnicodeNormalization};
+    use unicode_xid::UnicodeXID;
+
+    use crate::fikra_entities::{Token, TokenType, TokenValue, Trivia, TriviaKind};
+    use crate::fikra_errors::{LexError, LexErrorKind};
+    use crate::fikra_symbols::Symbol;
+
+    // Lexes the whole source up front, collecting every lexical error
+    pub fn tokenize(source: &str) -> Result<Vec<Token<'_>>, Vec<LexError>> {
         let mut tokens = Vec::new();
-        let mut chars = contents.char_indices().peekable();
-        let mut line = 1;
-        let mut column = 1;
+        let mut errors = Vec::new();
 
-        while let Some((i, c)) = chars.next() {
-            let token = match c {
-                c if c.is_whitespace() => {
-                    if c == '\n' {
-                        line += 1;
-                        column = 1;
-                    } else {
-                        column += 1;
+        for item in Lexer::new(source) {
+            match item {
+                Ok(token) => tokens.push(token),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(errors)
+        }
+    }
+
+    // Lexes in lossless mode. Joining the result with `render` gives back the source byte for byte,
+    // even where lexical errors were reported.
+    pub fn tokenize_lossless(source: &str) -> (Vec<Token<'_>>, Vec<LexError>) {
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+
+        for item in Lexer::lossless(source) {
+            match item {
+                Ok(token) => tokens.push(token),
+                Err(error) => errors.push(error),
+            }
+        }
+        (tokens, errors)
+    }
+
+    // Writes lossless tokens back out as source text
+    pub fn render(source: &str, tokens: &[Token]) -> String {
+        let mut output = String::with_capacity(source.len());
+        for token in tokens {
+            token.leading_trivia.iter().for_each(|piece| output.push_str(piece.text));
+            output.push_str(&source[token.span.clone()]);
+            token.trailing_trivia.iter().for_each(|piece| output.push_str(piece.text));
+        }
+        output
+    }
+
+    // Streaming lexer over a borrowed source. Tokens are produced on demand; identifiers are
+    // interned and strings borrow their text from the source unless escapes change it.
+    pub struct Lexer<'src> {
+        source: &'src str,
+        chars: Peekable<CharIndices<'src>>,
+        base: usize, // Byte offset in `source` where `chars` starts
+        line: usize,
+        column: usize,
+        // Type of the last token produced, used to tell a negative literal from a binary minus
+        prev: Option<TokenType>,
+        errors: VecDeque<LexError>,
+        // Doc comment text waiting to be attached to the next token
+        pending_doc: Option<Cow<'src, str>>,
+        // In lossless mode tokens carry their trivia and the stream ends with an EOF token
+        lossless: bool,
+        leading_trivia: Vec<Trivia<'src>>,
+        finished: bool,
+    }
+
+    impl<'src> Lexer<'src> {
+        pub fn new(source: &'src str) -> Self {
+            Lexer {
+                source,
+                chars: source.char_indices().peekable(),
+                base: 0,
+                line: 1,
+                column: 1,
+                prev: None,
+                errors: VecDeque::new(),
+                pending_doc: None,
+                lossless: false,
+                leading_trivia: Vec::new(),
+                finished: false,
+            }
+        }
+
+        // A lexer whose tokens carry their surrounding whitespace and comments as trivia, so that
+        // concatenating every token's trivia and text reproduces the source exactly
+        pub fn lossless(source: &'src str) -> Self {
+            Lexer { lossless: true, ..Lexer::new(source) }
+        }
+
+        // A lexer that starts at byte `offset` of `source`, in the state a lexer run from the
+        // beginning would be in after producing a token of type `prev` that ends there
+        pub(crate) fn resume(source: &'src str, offset: usize, line: usize, column: usize, prev: Option<TokenType>, lossless: bool) -> Self {
+            Lexer {
+                chars: source[offset..].char_indices().peekable(),
+                base: offset,
+                line,
+                column,
+                prev,
+                lossless,
+                ..Lexer::new(source)
+            }
+        }
+
+        // In lossless mode the trivia at the end of the file is carried by a final EOF token
+        fn end_of_input(&mut self, line: usize, column: usize) -> Option<Result<Token<'src>, LexError>> {
+            if !self.lossless || self.finished {
+                return None;
+            }
+            self.finished = true;
+
+            let mut token = Token::new(TokenType::EOF, None, line, column);
+            token.span = self.source.len()..self.source.len();
+            token.doc = self.pending_doc.take();
+            token.leading_trivia = std::mem::take(&mut self.leading_trivia);
+            Some(Ok(token))
+        }
+
+        // Line and column of the next unread character
+        pub(crate) fn position(&self) -> (usize, usize) {
+            (self.line, self.column)
+        }
+
+        // Byte offset of the next unread character
+        fn offset(&mut self) -> usize {
+            self.chars.peek().map_or(self.source.len(), |&(i, _)| self.base + i)
+        }
+
+        fn peek_char(&mut self) -> Option<char> {
+            self.chars.peek().map(|&(_, c)| c)
+        }
+
+        fn bump(&mut self) -> Option<(usize, char)> {
+            let (i, c) = self.chars.next()?;
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+            Some((self.base + i, c))
+        }
+
+        fn bump_while(&mut self, predicate: impl Fn(char) -> bool) {
+            while let Some(c) = self.peek_char() {
+                if !predicate(c) {
+                    break;
+                }
+                self.bump();
+            }
+        }
+
+        // Consumes the next character if it is `expected`
+        fn eat(&mut self, expected: char) -> bool {
+            if self.peek_char() == Some(expected) {
+                self.bump();
+                true
+            } else {
+                false
+            }
+        }
+
+        // Picks the two-character operator when `second` follows, otherwise the single-character one
+        fn one_or_two(&mut self, second: char, double: TokenType, single: TokenType) -> TokenType {
+            if self.eat(second) {
+                double
+            } else {
+                single
+            }
+        }
+
+        fn error(&mut self, kind: LexErrorKind, line: usize, column: usize, start: usize) {
+            let end = self.offset();
+            self.errors.push_back(LexError { kind, line, column, span: start..end });
+        }
+
+        // Called after `//` has been consumed. `///` (but not `////`) starts a doc comment.
+        // Returns whether it was a doc comment.
+        fn line_comment(&mut self) -> bool {
+            let is_doc = self.peek_char() == Some('/') && {
+                let mut lookahead = self.source[self.offset()..].chars().skip(1);
+                lookahead.next() != Some('/')
+            };
+
+            if is_doc {
+                self.bump();
+            }
+            let text_start = self.offset();
+            self.bump_while(|c| c != '\n');
+
+            if is_doc {
+                let text_end = self.offset();
+                self.add_doc_line(&self.source[text_start..text_end]);
+            }
+            is_doc
+        }
+
+        // Appends the text of one `///` line (without the slashes) to the pending doc comment
+        fn add_doc_line(&mut self, text: &'src str) {
+            let text = text.strip_prefix(' ').unwrap_or(text).trim_end();
+            match &mut self.pending_doc {
+                Some(doc) => {
+                    let doc = doc.to_mut();
+                    doc.push('\n');
+                    doc.push_str(text);
+                }
+                None => self.pending_doc = Some(Cow::Borrowed(text)),
+            }
+        }
+
+        // When resuming in lossless mode, doc comments in the trailing trivia of the token before
+        // the resume point belong to the next token and have to be carried over
+        pub(crate) fn carry_doc_comments(&mut self, trivia: &[Trivia<'src>]) {
+            for piece in trivia.iter().filter(|piece| piece.kind == TriviaKind::DocComment) {
+                self.add_doc_line(&piece.text[3..]);
+            }
+        }
+
+        // Called after `/*` has been consumed. Block comments nest.
+        fn block_comment(&mut self, line: usize, column: usize, start: usize) {
+            let mut depth = 1;
+            while depth > 0 {
+                match self.bump() {
+                    Some((_, '/')) if self.eat('*') => depth += 1,
+                    Some((_, '*')) if self.eat('/') => depth -= 1,
+                    Some(_) => {}
+                    None => {
+                        self.error(LexErrorKind::UnterminatedBlockComment, line, column, start);
+                        return;
+                    }
+                }
+            }
+        }
+
+        // Called after the opening `"` has been consumed. The text is only copied once an escape is seen.
+        fn string(&mut self, line: usize, column: usize, start: usize) -> Option<Token<'src>> {
+            let text_start = self.offset();
+            let mut decoded: Option<String> = None;
+            let mut valid = true;
+
+            let text_end = loop {
+                let escape_line = self.line;
+                let escape_column = self.column;
+                match self.bump() {
+                    Some((end, '"')) => break end,
+                    Some((escape_start, '\\')) => {
+                        let text = decoded.get_or_insert_with(|| self.source[text_start..escape_start].to_string());
+                        match self.escape() {
+                            Ok(c) => text.push(c),
+                            Err(kind) => {
+                                self.error(kind, escape_line, escape_column, escape_start);
+                                valid = false;
+                            }
+                        }
                     }
-                    continue;
-                }
-                c if c.is_alphabetic() => {
-                    let word_start = i;
-                    let start_column = column;
-                    while let Some(&(_, next_c)) = chars.peek() {
-                        if !next_c.is_alphanumeric() {
-                            break;
+                    Some((_, c)) => {
+                        if let Some(text) = &mut decoded {
+                            text.push(c);
                         }
-                        chars.next();
-                        column += 1;
                     }
-                    let word = &contents[word_start..chars.peek().map_or(contents.len(), |&(i, _)| i)];
-                    match word {
-                        "return" => Token { _type: TokenType::Return, value: None, line, column: start_column },
-                        "let" => Token { _type: TokenType::Let, value: None, line, column: start_column },
-                        _ => Token { _type: TokenType::Ident, value: Some(TokenValue::Identifier(word.to_string())), line, column: start_column },
+                    None => {
+                        self.error(LexErrorKind::UnterminatedString, line, column, start);
+                        return None;
                     }
                 }
-                c if c.is_numeric() => {
-                    let num_start = i;
-                    let start_column = column;
-                    while let Some(&(_, next_c)) = chars.peek() {
-                        if !next_c.is_numeric() {
-                            break;
-                        }
-                        chars.next();
-                        column += 1;
+            };
+
+            let text = match decoded {
+                Some(text) => Cow::Owned(text),
+                None => Cow::Borrowed(&self.source[text_start..text_end]),
+            };
+            valid.then(|| Token::new(TokenType::StringLit, Some(TokenValue::Str(text)), line, column))
+        }
+
+        // Called after `r"` has been consumed. Raw strings take their contents verbatim.
+        fn raw_string(&mut self, line: usize, column: usize, start: usize) -> Option<Token<'src>> {
+            let text_start = self.offset();
+            self.bump_while(|c| c != '"');
+            let text = &self.source[text_start..self.offset()];
+
+            if !self.eat('"') {
+                self.error(LexErrorKind::UnterminatedString, line, column, start);
+                return None;
+            }
+            Some(Token::new(TokenType::StringLit, Some(TokenValue::Str(Cow::Borrowed(text))), line, column))
+        }
+
+        // Called after the opening `'` has been consumed. A character literal holds exactly one character.
+        fn char_literal(&mut self, line: usize, column: usize, start: usize) -> Option<Token<'src>> {
+            let escape_line = self.line;
+            let escape_column = self.column;
+            let value = match self.bump() {
+                Some((escape_start, '\\')) => match self.escape() {
+                    Ok(c) => Some(c),
+                    Err(kind) => {
+                        self.error(kind, escape_line, escape_column, escape_start);
+                        None
                     }
-                    let number = &contents[num_start..chars.peek().map_or(contents.len(), |&(i, _)| i)];
-                    Token {
-                        _type: TokenType::Int32Lit,
-                        value: Some(TokenValue::Int32(number.parse().unwrap())),
-                        line,
-                        column: start_column,
+                },
+                Some((_, '\'')) => {
+                    self.error(LexErrorKind::EmptyCharLiteral, line, column, start);
+                    return None;
+                }
+                Some((_, '\n')) | None => {
+                    self.error(LexErrorKind::UnterminatedChar, line, column, start);
+                    return None;
+                }
+                Some((_, c)) => Some(c),
+            };
+
+            if !self.eat('\'') {
+                self.bump_while(|c| c != '\'' && c != '\n');
+                let kind = if self.eat('\'') { LexErrorKind::MultipleCharsInLiteral } else { LexErrorKind::UnterminatedChar };
+                self.error(kind, line, column, start);
+                return None;
+            }
+            value.map(|c| Token::new(TokenType::CharLit, Some(TokenValue::Char(c)), line, column))
+        }
+
+        // Called after a `\` inside a literal has been consumed
+        fn escape(&mut self) -> Result<char, LexErrorKind> {
+            match self.bump() {
+                Some((_, 'n')) => Ok('\n'),
+                Some((_, 't')) => Ok('\t'),
+                Some((_, 'r')) => Ok('\r'),
+                Some((_, '0')) => Ok('\0'),
+                Some((_, '\\')) => Ok('\\'),
+                Some((_, '"')) => Ok('"'),
+                Some((_, '\'')) => Ok('\''),
+                Some((_, 'u')) => self.unicode_escape(),
+                Some((_, c)) => Err(LexErrorKind::InvalidEscape(c.to_string())),
+                None => Err(LexErrorKind::InvalidEscape(String::new())),
+            }
+        }
+
+        // Called after `\u` has been consumed; expects `{` 1-6 hex digits `}`
+        fn unicode_escape(&mut self) -> Result<char, LexErrorKind> {
+            if !self.eat('{') {
+                return Err(LexErrorKind::InvalidUnicodeEscape(String::new()));
+            }
+            let digits_start = self.offset();
+            self.bump_while(|c| c.is_ascii_hexdigit());
+            let digits = &self.source[digits_start..self.offset()];
+
+            if !self.eat('}') || digits.is_empty() || digits.len() > 6 {
+                return Err(LexErrorKind::InvalidUnicodeEscape(digits.to_string()));
+            }
+            u32::from_str_radix(digits, 16)
+                .ok()
+                .and_then(char::from_u32)
+                .ok_or_else(|| LexErrorKind::InvalidUnicodeEscape(digits.to_string()))
+        }
+
+        // Whether the last token can end an operand, in which case a following `-` is binary
+        fn prev_is_operand(&self) -> bool {
+            self.prev.is_some_and(|_type| {
+                _type.is_literal() || matches!(_type, TokenType::Ident | TokenType::CloseParen | TokenType::CloseBracket)
+            })
+        }
+
+        // Called after the first digit (or the `.` of a `.5` style float) has been consumed,
+        // along with a leading `-` for negative literals. Integers take `0x`/`0o`/`0b` prefixes,
+        // `_` separators and `i8`..`u64` suffixes; decimal floats take fractions, exponents and `f32`/`f64`.
+        fn number(&mut self, negative: bool, line: usize, column: usize, start: usize) -> Option<Token<'src>> {
+            let mut digits_start = self.offset() - 1;
+            let first = &self.source[digits_start..digits_start + 1];
+            let radix = match (first, self.peek_char()) {
+                ("0", Some('x')) => 16,
+                ("0", Some('o')) => 8,
+                ("0", Some('b')) => 2,
+                _ => 10,
+            };
+            if radix != 10 {
+                self.bump();
+                digits_start = self.offset();
+            }
+
+            let mut is_float = first == ".";
+            if radix == 10 {
+                self.bump_while(|c| c.is_ascii_digit() || c == '_');
+                if !is_float && self.peek_char() == Some('.') && self.fraction_follows() {
+                    self.bump();
+                    self.bump_while(|c| c.is_ascii_digit() || c == '_');
+                    is_float = true;
+                }
+                if self.exponent_follows() {
+                    self.bump();
+                    if !self.eat('+') {
+                        self.eat('-');
                     }
+                    self.bump_while(|c| c.is_ascii_digit() || c == '_');
+                    is_float = true;
+                }
+            }
+            self.bump_while(|c| c.is_ascii_alphanumeric() || c == '_');
+
+            let literal = &self.source[start..self.offset()];
+            let body = &self.source[digits_start..self.offset()];
+            let suffix_markers: &[char] = if radix == 10 { &['i', 'u', 'f'] } else { &['i', 'u'] };
+            let (digits, suffix) = body.split_at(body.find(suffix_markers).unwrap_or(body.len()));
+
+            if is_float || suffix.starts_with('f') {
+                return self.float(literal, suffix, line, column, start);
+            }
+
+            let digits: String = digits.chars().filter(|&c| c != '_').collect();
+            if let Some(bad) = digits.chars().find(|c| !c.is_digit(radix)) {
+                self.error(LexErrorKind::InvalidDigit(bad, radix), line, column, start);
+                return None;
+            }
+            if digits.is_empty() {
+                self.error(LexErrorKind::InvalidIntegerLiteral(literal.to_string()), line, column, start);
+                return None;
+            }
+
+            let magnitude = i128::from_str_radix(&digits, radix).ok();
+            let value = magnitude.map(|n| if negative { -n } else { n });
+            let (_type, width) = match suffix {
+                "" | "i32" => (TokenType::Int32Lit, "i32"),
+                "i8" => (TokenType::Int8Lit, "i8"),
+                "i16" => (TokenType::Int16Lit, "i16"),
+                "i64" => (TokenType::Int64Lit, "i64"),
+                "u8" => (TokenType::UInt8Lit, "u8"),
+                "u16" => (TokenType::UInt16Lit, "u16"),
+                "u32" => (TokenType::UInt32Lit, "u32"),
+                "u64" => (TokenType::UInt64Lit, "u64"),
+                _ => {
+                    self.error(LexErrorKind::InvalidIntegerSuffix(suffix.to_string()), line, column, start);
+                    return None;
+                }
+            };
+
+            match value.and_then(|n| TokenValue::integer(&_type, n)) {
+                Some(value) => Some(Token::new(_type, Some(value), line, column)),
+                None => {
+                    let kind = LexErrorKind::IntegerOutOfRange(literal.to_string(), width.to_string());
+                    self.error(kind, line, column, start);
+                    None
                 }
-                '(' => Token { _type: TokenType::OpenParen, value: None, line, column },
-                ')' => Token { _type: TokenType::CloseParen, value: None, line, column },
-                '=' => Token { _type: TokenType::Eq, value: None, line, column },
-                '+' => Token { _type: TokenType::Plus, value: None, line, column },
-                '*' => Token { _type: TokenType::Star, value: None, line, column },
-                '/' => Token { _type: TokenType::Slash, value: None, line, column },
-                '-' => Token { _type: TokenType::Minus, value: None, line, column },
-                ';' => Token { _type: TokenType::Semi, value: None, line, column },
-                '{' => Token { _type: TokenType::OpenBrace, value: None, line, column },
-                '}' => Token { _type: TokenType::CloseBrace, value: None, line, column },
+            }
+        }
+
+        // `literal` is the whole float literal, `suffix` its trailing `f32`/`f64` if any
+        fn float(&mut self, literal: &str, suffix: &str, line: usize, column: usize, start: usize) -> Option<Token<'src>> {
+            let text: String = literal[..literal.len() - suffix.len()].chars().filter(|&c| c != '_').collect();
+
+            let value = match suffix {
+                "f32" => text.parse::<f32>().ok().filter(|n| n.is_finite()).map(TokenValue::Float32),
+                "" | "f64" => text.parse::<f64>().ok().filter(|n| n.is_finite()).map(TokenValue::Float64),
                 _ => {
-                    eprintln!("Unexpected character: {} at line {}, column {}", c, line, column);
-                    column += 1;
-                    continue;
+                    self.error(LexErrorKind::InvalidFloatLiteral(literal.to_string()), line, column, start);
+                    return None;
+                }
+            };
+
+            match value {
+                Some(value) => Some(Token::new(TokenType::FloatLit, Some(value), line, column)),
+                None => {
+                    self.error(LexErrorKind::InvalidFloatLiteral(literal.to_string()), line, column, start);
+                    None
+                }
+            }
+        }
+
+        // With a `.` next, whether it starts a fraction rather than a `..` range or a member access
+        fn fraction_follows(&mut self) -> bool {
+            let after_dot = self.source[self.offset()..].chars().nth(1);
+            !after_dot.is_some_and(|c| c == '.' || c == '_' || c.is_xid_start())
+        }
+
+        // Whether an `e`/`E` exponent with at least one digit comes next
+        fn exponent_follows(&mut self) -> bool {
+            let offset = self.offset();
+            let mut rest = self.source[offset..].chars();
+            if !matches!(rest.next(), Some('e' | 'E')) {
+                return false;
+            }
+            match rest.next() {
+                Some('+' | '-') => rest.next().is_some_and(|c| c.is_ascii_digit()),
+                next => next.is_some_and(|c| c.is_ascii_digit()),
+            }
+        }
+
+        // Consumes one piece of whitespace or one comment, if that is what comes next.
+        // A whitespace piece ends after a newline, so trailing trivia can stop at the end of the line.
+        fn trivia(&mut self) -> Option<Trivia<'src>> {
+            let line = self.line;
+            let column = self.column;
+            let start = self.offset();
+            let rest = &self.source[start..];
+
+            let kind = if rest.starts_with(char::is_whitespace) {
+                while let Some(c) = self.peek_char().filter(|c| c.is_whitespace()) {
+                    self.bump();
+                    if c == '\n' {
+                        break;
+                    }
+                }
+                TriviaKind::Whitespace
+            } else if rest.starts_with("//") {
+                self.bump();
+                self.bump();
+                if self.line_comment() {
+                    TriviaKind::DocComment
+                } else {
+                    TriviaKind::LineComment
                 }
+            } else if rest.starts_with("/*") {
+                self.bump();
+                self.bump();
+                self.block_comment(line, column, start);
+                TriviaKind::BlockComment
+            } else {
+                return None;
             };
-            tokens.push(token);
-            column += 1;
+
+            Some(Trivia { kind, text: &self.source[start..self.offset()] })
+        }
+
+        // Trivia after a token up to and including the end of its line
+        fn trailing_trivia(&mut self) -> Vec<Trivia<'src>> {
+            let mut trailing = Vec::new();
+            while let Some(piece) = self.trivia() {
+                let ends_line = piece.text.contains('\n');
+                trailing.push(piece);
+                if ends_line {
+                    break;
+                }
+            }
+            trailing
         }
 
-        tokens
+        // Lexes the token starting with `c`. Returns None after reporting an error.
+        fn scan(&mut self, c: char, line: usize, column: usize, start: usize) -> Option<Token<'src>> {
+            let token = match c {
+                '"' => self.string(line, column, start)?,
+                '\'' => self.char_literal(line, column, start)?,
+                'r' if self.peek_char() == Some('"') => {
+                    self.bump();
+                    self.raw_string(line, column, start)?
+                }
+                c if c == '_' || c.is_xid_start() => {
+                    self.bump_while(|c| c.is_xid_continue());
+                    // Identifiers are compared in NFC so that visually identical spellings match
+                    let raw = &self.source[start..self.offset()];
+                    let word: Cow<'src, str> = if is_nfc(raw) { Cow::Borrowed(raw) } else { Cow::Owned(raw.nfc().collect()) };
+                    match TokenType::from_keyword(&word) {
+                        Some(TokenType::BoolLit) => {
+                            Token::new(TokenType::BoolLit, Some(TokenValue::Boolean(word == "true")), line, column)
+                        }
+                        Some(keyword) => Token::new(keyword, None, line, column),
+                        None if TokenType::is_reserved_word(&word) => {
+                            self.error(LexErrorKind::ReservedWord(word.into_owned()), line, column, start);
+                            return None;
+                        }
+                        None => Token::new(TokenType::Ident, Some(TokenValue::Identifier(Symbol::intern(&word))), line, column),
+                    }
+                }
+                c if c.is_ascii_digit() => self.number(false, line, column, start)?,
+                '.' if self.peek_char().is_some_and(|c| c.is_ascii_digit()) && !self.prev_is_operand() => {
+                    self.number(false, line, column, start)?
+                }
+                '-' if self.peek_char().is_some_and(|c| c.is_ascii_digit()) && !self.prev_is_operand() => {
+                    self.bump();
+                    self.number(true, line, column, start)?
+                }
+                '(' => Token::new(TokenType::OpenParen, None, line, column),
+                ')' => Token::new(TokenType::CloseParen, None, line, column),
+                '{' => Token::new(TokenType::OpenBrace, None, line, column),
+                '}' => Token::new(TokenType::CloseBrace, None, line, column),
+                '[' => Token::new(TokenType::OpenBracket, None, line, column),
+                ']' => Token::new(TokenType::CloseBracket, None, line, column),
+                ';' => Token::new(TokenType::Semi, None, line, column),
+                ',' => Token::new(TokenType::Comma, None, line, column),
+                '.' if self.eat('.') => Token::new(self.one_or_two('=', TokenType::DotDotEq, TokenType::DotDot), None, line, column),
+                '.' => Token::new(TokenType::Dot, None, line, column),
+                '+' => Token::new(self.one_or_two('=', TokenType::PlusEq, TokenType::Plus), None, line, column),
+                '-' => Token::new(self.one_or_two('=', TokenType::MinusEq, TokenType::Minus), None, line, column),
+                '*' => Token::new(self.one_or_two('=', TokenType::StarEq, TokenType::Star), None, line, column),
+                '/' => Token::new(self.one_or_two('=', TokenType::SlashEq, TokenType::Slash), None, line, column),
+                '%' => Token::new(self.one_or_two('=', TokenType::PercentEq, TokenType::Percent), None, line, column),
+                '^' => Token::new(TokenType::Caret, None, line, column),
+                '=' => Token::new(self.one_or_two('=', TokenType::EqEq, TokenType::Eq), None, line, column),
+                '!' => Token::new(self.one_or_two('=', TokenType::NotEq, TokenType::Not), None, line, column),
+                '<' => Token::new(self.one_or_two('=', TokenType::LtEq, TokenType::Lt), None, line, column),
+                '>' => Token::new(self.one_or_two('=', TokenType::GtEq, TokenType::Gt), None, line, column),
+                '&' if self.eat('&') => Token::new(TokenType::And, None, line, column),
+                '|' if self.eat('|') => Token::new(TokenType::Or, None, line, column),
+                _ => {
+                    self.error(LexErrorKind::UnexpectedCharacter(c), line, column, start);
+                    return None;
+                }
+            };
+            Some(token)
+        }
+    }
+
+    impl<'src> Iterator for Lexer<'src> {
+        type Item = Result<Token<'src>, LexError>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            loop {
+                while let Some(piece) = self.trivia() {
+                    if self.lossless {
+                        self.leading_trivia.push(piece);
+                    }
+                }
+                if let Some(error) = self.errors.pop_front() {
+                    return Some(Err(error));
+                }
+
+                let line = self.line;
+                let column = self.column;
+                let Some((start, c)) = self.bump() else {
+                    return self.end_of_input(line, column);
+                };
+
+                match self.scan(c, line, column, start) {
+                    Some(mut token) => {
+                        token.span = start..self.offset();
+                        token.doc = self.pending_doc.take();
+                        if self.lossless {
+                            token.leading_trivia = std::mem::take(&mut self.leading_trivia);
+                            token.trailing_trivia = self.trailing_trivia();
+                        }
+                        self.prev = Some(token._type);
+                        return Some(Ok(token));
+                    }
+                    // Text that failed to lex is kept as trivia so the lossless stream stays complete
+                    None if self.lossless => {
+                        let text = &self.source[start..self.offset()];
+                        self.leading_trivia.push(Trivia { kind: TriviaKind::Skipped, text });
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    // Token source for the parser. Pulls tokens from the lexer lazily and sets lexical
+    // errors aside so that they can all be reported once parsing is done.
+    pub struct TokenStream<'src> {
+        lexer: Lexer<'src>,
+        peeked: Option<Token<'src>>,
+        errors: Vec<LexError>,
+    }
+
+    impl<'src> TokenStream<'src> {
+        pub fn new(source: &'src str) -> Self {
+            TokenStream { lexer: Lexer::new(source), peeked: None, errors: Vec::new() }
+        }
+
+        pub fn peek(&mut self) -> Option<&Token<'src>> {
+            if self.peeked.is_none() {
+                self.peeked = self.pull();
+            }
+            self.peeked.as_ref()
+        }
+
+        // Line and column of the next token, or of the end of the input once it is reached
+        pub fn position(&mut self) -> (usize, usize) {
+            match self.peek() {
+                Some(token) => (token.line, token.column),
+                None => self.lexer.position(),
+            }
+        }
+
+        fn pull(&mut self) -> Option<Token<'src>> {
+            for item in self.lexer.by_ref() {
+                match item {
+                    Ok(token) => return Some(token),
+                    Err(error) => self.errors.push(error),
+                }
+            }
+            None
+        }
+
+        // Lexes whatever input is left and returns every lexical error encountered
+        pub fn finish(mut self) -> Vec<LexError> {
+            while self.pull().is_some() {}
+            self.errors
+        }
+    }
+
+    impl<'src> Iterator for TokenStream<'src> {
+        type Item = Token<'src>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.peeked.take().or_else(|| self.pull())
+        }
     }
-}
\ No newline at end of file
+}
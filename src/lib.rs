@@ -1,3 +1,10 @@
+pub mod fikra_entities;
+pub mod fikra_tokenization;
+pub mod fikra_parser;
+pub mod fikra_generator;
+pub mod fikra_errors;
+pub mod fikra_symbols;
+
 
 pub fn parse_config(args: &[String]) ->  &str {
     
@@ -1,17 +1,12 @@
 use std::{env, fs, io::Write, process::Command};
 use fikra:: parse_config;
-use fikra_parser::node::AstArena;
-mod fikra_entities;
-mod fikra_tokenization;
-mod fikra_parser;
-mod fikra_generator;
-mod fikra_errors;
-
-use fikra_tokenization::tokenization::tokens;  
+use fikra::fikra_parser::node::AstArena;
+use fikra::fikra_errors::ParseError;
+
 //use fikra_tokenization::to_asm::fikra_tokens_to_asm;
 
-use fikra_parser::parser::Parser;
-use fikra_generator::generator::Generator;
+use fikra::fikra_parser::parser::Parser;
+use fikra::fikra_generator::generator::Generator;
 
 
 fn main() {
@@ -29,17 +24,24 @@ fn run() -> Result<(), String> {
 
     println!("{file_path}");
 
-    let contents = fs::read_to_string(&file_path)
+    let contents = fs::read_to_string(file_path)
         .map_err(|e| format!("Error reading file {}: {}", file_path, e))?;
 
-    let tokens = tokens::tokenize(contents);
-
     let ast_arena = AstArena::new();
 
-    let mut token_parser = Parser::new(&tokens,&ast_arena);
+    let mut token_parser = Parser::new(&contents,&ast_arena);
 
     let node_return = token_parser.parse_prog()
-    .map_err(|e| format!("Parsing error: {}", e))?;
+    .map_err(|e| match e {
+        ParseError::Lexical(errors) => {
+            let messages: Vec<String> = errors.iter().map(|e| format!("Lexical error: {}", e)).collect();
+            format!("{} lexical error(s) in {}\n{}", errors.len(), file_path, messages.join("\n"))
+        }
+        ParseError::Syntax(errors) => {
+            let messages: Vec<String> = errors.iter().map(|e| format!("Syntax error: {}", e)).collect();
+            format!("{} syntax error(s) in {}\n{}", errors.len(), file_path, messages.join("\n"))
+        }
+    })?;
 
     let output_generator = Generator::new(node_return);
     let output_tree = output_generator.generate_program()
@@ -0,0 +1,206 @@
+use fikra::fikra_errors::GeneratorError;
+use fikra::fikra_generator::generator::Generator;
+use fikra::fikra_parser::node::AstArena;
+use fikra::fikra_parser::parser::Parser;
+
+fn compile(source: &str) -> String {
+    let ast_arena = AstArena::new();
+    let prog = Parser::new(source, &ast_arena).parse_prog().unwrap_or_else(|error| panic!("{}", error));
+    Generator::new(prog).generate_program().unwrap_or_else(|error| panic!("{}", error))
+}
+
+fn compile_error(source: &str) -> GeneratorError {
+    let ast_arena = AstArena::new();
+    let prog = Parser::new(source, &ast_arena).parse_prog().unwrap_or_else(|error| panic!("{}", error));
+    match Generator::new(prog).generate_program() {
+        Err(error) => error,
+        Ok(asm) => panic!("expected {:?} not to compile, got\n{}", source, asm),
+    }
+}
+
+// Label defined on the first line of `asm` that starts with `prefix`
+fn label_of(asm: &str, prefix: &str) -> String {
+    asm.lines()
+        .find(|line| line.starts_with(prefix))
+        .and_then(|line| line.strip_suffix(':'))
+        .unwrap_or_else(|| panic!("no label starting with {:?} in\n{}", prefix, asm))
+        .to_string()
+}
+
+// Position of the first line of `asm` that starts with `prefix`, ignoring indentation
+fn line_of(asm: &str, prefix: &str) -> usize {
+    asm.lines()
+        .position(|line| line.trim_start().starts_with(prefix))
+        .unwrap_or_else(|| panic!("no line starting with {:?} in\n{}", prefix, asm))
+}
+
+#[test]
+fn logical_operators_jump_over_their_right_operand() {
+    let asm = compile("let a = 0;\nreturn a && 1 / a;");
+    let division = line_of(&asm, "idiv rbx");
+    assert!(line_of(&asm, "jz logical_end_") < division, "in\n{}", asm);
+    assert!(division < line_of(&asm, "logical_end_"), "in\n{}", asm);
+
+    let asm = compile("let a = 1;\nreturn a || 1 / 0;");
+    assert!(line_of(&asm, "jnz logical_end_") < line_of(&asm, "idiv rbx"), "in\n{}", asm);
+}
+
+#[test]
+fn negative_exponents_jump_to_a_runtime_error() {
+    let asm = compile("let e = 0 - 1;\nreturn 2 ^ e;");
+    let jump = asm.lines().map(str::trim).find(|line| line.starts_with("js ")).unwrap_or_else(|| panic!("no sign check in\n{}", asm));
+    let label = jump.trim_start_matches("js ");
+    assert!(asm.lines().any(|line| line.trim() == format!("{}:", label)), "in\n{}", asm);
+    assert!(asm.contains(&format!("{}_message: db \"runtime error: negative exponent in integer exponentiation\", 10", label)), "in\n{}", asm);
+}
+
+#[test]
+fn constant_remainders_are_folded() {
+    for source in ["return (2 + 3) % 2;", "return 10 % (1 + 2);"] {
+        let asm = compile(source);
+        assert!(asm.contains("mov rax, 1\n"), "for {:?} in\n{}", source, asm);
+        assert!(!asm.contains("idiv"), "for {:?} in\n{}", source, asm);
+    }
+    assert!(compile("return (2 ^ 3 ^ 2) % 1000;").contains("mov rax, 512\n"));
+    assert!(compile("return 7 % (1 - 1);").contains("idiv rbx"));
+}
+
+#[test]
+fn if_statements_jump_past_the_branch_not_taken() {
+    let asm = compile("let a = 2;\nif a == 1 { return 10; } else { return 20; }\nreturn 0;");
+    let order = [
+        line_of(&asm, "jz else_"),
+        line_of(&asm, "mov rax, 10"),
+        line_of(&asm, "jmp if_end_"),
+        line_of(&asm, "else_"),
+        line_of(&asm, "mov rax, 20"),
+        line_of(&asm, "if_end_"),
+    ];
+    assert!(order.windows(2).all(|pair| pair[0] < pair[1]), "in\n{}", asm);
+}
+
+#[test]
+fn break_and_continue_jump_to_the_loop_labels() {
+    let asm = compile("let a = 1;\nwhile a { if a == 2 { continue; } break; }\nreturn 0;");
+    let start = label_of(&asm, "while_start_");
+    let end = label_of(&asm, "while_end_");
+    let lines: Vec<&str> = asm.lines().map(str::trim).collect();
+    // `continue` and the jump back at the end of the body
+    assert_eq!(lines.iter().filter(|line| **line == format!("jmp {}", start)).count(), 2, "in\n{}", asm);
+    assert_eq!(lines.iter().filter(|line| **line == format!("jmp {}", end)).count(), 1, "in\n{}", asm);
+    assert!(lines.contains(&format!("jz {}", end).as_str()), "in\n{}", asm);
+
+    assert_eq!(compile_error("let a = 1;\nif a { break; }").to_string(), "`break` outside of a loop at 2:8");
+}
+
+#[test]
+fn for_loops_stop_at_the_end_of_their_range() {
+    let asm = compile("let n = 3;\nfor i in 0..n { }\nreturn 0;");
+    assert!(line_of(&asm, "jge for_end_") > line_of(&asm, "for_start_"), "in\n{}", asm);
+
+    let asm = compile("let n = 3;\nfor i in 0..=n step n { }\nreturn 0;");
+    assert!(line_of(&asm, "jg for_end_") > line_of(&asm, "for_start_"), "in\n{}", asm);
+    assert!(line_of(&asm, "jle runtime_error_") < line_of(&asm, "for_start_"), "in\n{}", asm);
+    assert!(asm.contains("db \"runtime error: for loop step must be positive\", 10"), "in\n{}", asm);
+}
+
+#[test]
+fn only_mutable_variables_can_be_assigned() {
+    let asm = compile("let mut x = 1;\nx += 2;\nreturn x;");
+    assert!(asm.contains("mov QWORD [rbp - 8], rax\n"), "in\n{}", asm);
+
+    assert_eq!(
+        compile_error("let x = 1;\nx += 2;").to_string(),
+        "Cannot assign to immutable variable x at 2:1; declare it with `let mut`",
+    );
+}
+
+#[test]
+fn functions_keep_their_frame_in_rbp() {
+    let asm = compile("fn add_one(a, b) { return a + b; }\nfn main() { return add_one(1, 2); }");
+    let lines: Vec<&str> = asm.lines().map(str::trim).collect();
+    let start = line_of(&asm, "fn_add__one:");
+    assert_eq!(lines[start + 1..start + 5], ["push rbp", "mov rbp, rsp", "push QWORD [rbp + 24]", "push QWORD [rbp + 16]"]);
+
+    // Every return, and the end of the body, restores the caller's frame
+    let returns = lines.windows(3).filter(|window| *window == ["mov rsp, rbp", "pop rbp", "ret"]).count();
+    assert_eq!(returns, lines.iter().filter(|line| **line == "ret").count());
+
+    let call = line_of(&asm, "call fn_add__one");
+    assert_eq!(lines[call + 1], "add rsp, 16", "in\n{}", asm);
+}
+
+#[test]
+fn array_lengths_may_be_constant_expressions() {
+    let asm = compile("let a = [0; 4 * 4];\nreturn a[15];");
+    assert!(asm.contains("cmp rax, 16\n"), "in\n{}", asm);
+    for source in ["let n = 4;\nlet a = [0; n];", "let x = 1;\nlet a = [0; 1 - 2];"] {
+        assert_eq!(
+            compile_error(source).to_string(),
+            "Invalid array literal at 2:9: the length must be a non-negative integer constant",
+            "for {:?}", source,
+        );
+    }
+}
+
+#[test]
+fn indices_are_checked_against_the_array_length() {
+    let asm = compile("let mut a = [1, 2, 3];\nlet i = 3;\na[i] = 4;\nreturn a[i];");
+    let lines: Vec<&str> = asm.lines().map(str::trim).collect();
+    let check = line_of(&asm, "cmp rax, 3");
+    let label = lines[check + 1].strip_prefix("jae ").unwrap_or_else(|| panic!("no bounds check in\n{}", asm));
+    assert!(lines.contains(&format!("{}:", label).as_str()), "in\n{}", asm);
+    assert!(asm.contains(&format!("{}_message: db \"runtime error: index out of bounds for array a at line 3\", 10", label)), "in\n{}", asm);
+}
+
+#[test]
+fn character_literals_compile_to_their_code_points() {
+    let asm = compile("return 'a' + 1;");
+    assert!(asm.contains("mov rax, 97\n"), "in\n{}", asm);
+    assert!(compile("return '\\u{1F600}';").contains("mov rax, 128512\n"));
+}
+
+#[test]
+fn unary_operators_negate_and_test_for_zero() {
+    let asm = compile("let a = 3;\nreturn -a;");
+    let lines: Vec<&str> = asm.lines().map(str::trim).collect();
+    let neg = line_of(&asm, "neg rax");
+    assert_eq!(lines[neg - 1..=neg + 1], ["pop rax", "neg rax", "push rax"], "in\n{}", asm);
+
+    let asm = compile("let a = 3;\nreturn !a;");
+    let lines: Vec<&str> = asm.lines().map(str::trim).collect();
+    let not = line_of(&asm, "xor rbx, rbx");
+    assert_eq!(lines[not - 1..=not + 3], ["pop rax", "xor rbx, rbx", "test rax, rax", "sete bl", "push rbx"], "in\n{}", asm);
+}
+
+#[test]
+fn loops_on_true_run_until_break() {
+    let asm = compile("while true { break; }\nreturn 0;");
+    let start = line_of(&asm, "while_start_");
+    let lines: Vec<&str> = asm.lines().map(str::trim).collect();
+    assert_eq!(lines[start + 1..start + 5], ["mov rax, 1", "push rax", "pop rax", "test rax, rax"], "in\n{}", asm);
+    let end = label_of(&asm, "while_end_");
+    assert!(lines.contains(&format!("jmp {}", end).as_str()), "in\n{}", asm);
+
+    let asm = compile("if false { return 1; }\nreturn 2;");
+    assert_eq!(line_of(&asm, "mov rax, 0") + 4, line_of(&asm, "jz else_"), "in\n{}", asm);
+}
+
+#[test]
+fn call_statements_drop_the_returned_value() {
+    let asm = compile("fn f() { return 1; }\nfn main() { f(); return 0; }");
+    let lines: Vec<&str> = asm.lines().map(str::trim).collect();
+    let call = line_of(&asm, "call fn_f");
+    assert_eq!(lines[call + 1..call + 3], ["push rax", "add rsp, 8"], "in\n{}", asm);
+}
+
+#[test]
+fn compound_assignments_to_elements_check_the_index_once() {
+    let asm = compile("fn f() { return 1; }\nfn main() { let mut a = [1, 2, 3]; a[f()] *= 5; return a[1]; }");
+    let lines: Vec<&str> = asm.lines().map(str::trim).collect();
+    assert_eq!(lines.iter().filter(|line| **line == "call fn_f").count(), 1, "in\n{}", asm);
+    // Once for the update and once for the read in the return
+    assert_eq!(lines.iter().filter(|line| **line == "cmp rax, 3").count(), 2, "in\n{}", asm);
+    let call = line_of(&asm, "call fn_f");
+    assert!(line_of(&asm, "mul rbx") > call, "in\n{}", asm);
+}
@@ -0,0 +1,61 @@
+use fikra::fikra_tokenization::incremental::relex::{relex, TextEdit};
+use fikra::fikra_tokenization::tokenization::tokens::{tokenize_lossless, Lexer};
+
+const SOURCE: &str = "/// the answer\nlet x = 5-(1+3)/2; // note\n{\n    let s = \"a\\tb\";\n    /* block */ let y = -1i64 + 'c';\n}\nreturn x;\n";
+
+fn lex(source: &str) -> Vec<fikra::fikra_entities::Token<'_>> {
+    Lexer::new(source).filter_map(Result::ok).collect()
+}
+
+// Re-lexing after an edit must give the same tokens as lexing the edited source from scratch
+fn assert_matches_full_lex(old_source: &str, edit: TextEdit) {
+    let new_source = edit.apply(old_source);
+
+    let old_tokens = lex(old_source);
+    let relexed = relex(old_source, &old_tokens, &edit, &new_source);
+    assert_eq!(relexed.tokens, lex(&new_source), "edit {:?}", edit);
+
+    let (old_tokens, _) = tokenize_lossless(old_source);
+    let relexed = relex(old_source, &old_tokens, &edit, &new_source);
+    assert_eq!(relexed.tokens, tokenize_lossless(&new_source).0, "lossless edit {:?}", edit);
+}
+
+#[test]
+fn edit_inside_a_line_only_relexes_nearby_tokens() {
+    let old_tokens = lex(SOURCE);
+    let start = SOURCE.find("1+3").unwrap();
+    let edit = TextEdit { range: start..start + 1, replacement: "100" };
+    let new_source = edit.apply(SOURCE);
+
+    let relexed = relex(SOURCE, &old_tokens, &edit, &new_source);
+    assert_eq!(relexed.tokens, lex(&new_source));
+    assert!(relexed.changed.len() <= 8, "re-lexed {:?} of {}", relexed.changed, old_tokens.len());
+    assert_eq!(relexed.changed.len() as isize - relexed.replaced.len() as isize, 0);
+}
+
+#[test]
+fn edits_that_change_token_boundaries() {
+    let quote = SOURCE.find('"').unwrap();
+    let cases = [
+        TextEdit { range: 4..5, replacement: "xy_z" },              // rename
+        TextEdit { range: 0..0, replacement: "/* " },               // unterminated comment swallows the file
+        TextEdit { range: quote..quote + 1, replacement: "" },      // string quote removed
+        TextEdit { range: 0..15, replacement: "" },                 // doc comment removed
+        TextEdit { range: 16..16, replacement: "\n\n  " },          // lines inserted
+        TextEdit { range: SOURCE.len()..SOURCE.len(), replacement: " x" },
+    ];
+    for edit in cases {
+        assert_matches_full_lex(SOURCE, edit);
+    }
+}
+
+#[test]
+fn every_single_character_deletion_and_insertion() {
+    for (i, _) in SOURCE.char_indices() {
+        let next = SOURCE[i..].chars().next().map_or(i, |c| i + c.len_utf8());
+        assert_matches_full_lex(SOURCE, TextEdit { range: i..next, replacement: "" });
+        for insert in ["-", "/", "\"", "9", "\n", "é"] {
+            assert_matches_full_lex(SOURCE, TextEdit { range: i..i, replacement: insert });
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use fikra::fikra_entities::{TokenType, TokenValue, KEYWORDS, RESERVED_WORDS};
+use fikra::fikra_errors::LexErrorKind;
+use fikra::fikra_tokenization::tokenization::tokens::tokenize;
+
+fn types(source: &str) -> Vec<TokenType> {
+    tokenize(source).unwrap().iter().map(|token| token._type).collect()
+}
+
+fn single_value(source: &str) -> TokenValue<'_> {
+    let mut tokens = tokenize(source).unwrap();
+    assert_eq!(tokens.len(), 1, "for {:?}", source);
+    tokens.remove(0).value.unwrap()
+}
+
+fn single_error(source: &str) -> LexErrorKind {
+    let mut errors = tokenize(source).unwrap_err();
+    assert_eq!(errors.len(), 1, "for {:?}", source);
+    errors.remove(0).kind
+}
+
+#[test]
+fn integer_literals_take_radix_prefixes_separators_and_suffixes() {
+    assert_eq!(single_value("0xff"), TokenValue::Int32(255));
+    assert_eq!(single_value("0o17"), TokenValue::Int32(15));
+    assert_eq!(single_value("0b1010"), TokenValue::Int32(10));
+    assert_eq!(single_value("1_000_000"), TokenValue::Int32(1_000_000));
+    assert_eq!(single_value("255u8"), TokenValue::UInt8(255));
+    assert_eq!(single_value("0xffff_ffffu32"), TokenValue::UInt32(u32::MAX));
+    assert_eq!(single_value("-128i8"), TokenValue::Int8(-128));
+    assert_eq!(single_value("-9223372036854775808i64"), TokenValue::Int64(i64::MIN));
+}
+
+#[test]
+fn integer_literals_must_fit_their_width() {
+    assert_eq!(single_error("256u8"), LexErrorKind::IntegerOutOfRange("256u8".to_string(), "u8".to_string()));
+    assert_eq!(single_error("-129i8"), LexErrorKind::IntegerOutOfRange("-129i8".to_string(), "i8".to_string()));
+    assert_eq!(single_error("2147483648"), LexErrorKind::IntegerOutOfRange("2147483648".to_string(), "i32".to_string()));
+}
+
+#[test]
+fn malformed_integer_literals_are_rejected() {
+    assert_eq!(single_error("0b102"), LexErrorKind::InvalidDigit('2', 2));
+    assert_eq!(single_error("0o8"), LexErrorKind::InvalidDigit('8', 8));
+    assert_eq!(single_error("0x"), LexErrorKind::InvalidIntegerLiteral("0x".to_string()));
+    assert_eq!(single_error("12i7"), LexErrorKind::InvalidIntegerSuffix("i7".to_string()));
+}
+
+#[test]
+fn float_literals_take_fractions_exponents_and_suffixes() {
+    assert_eq!(single_value("3."), TokenValue::Float64(3.0));
+    assert_eq!(single_value(".5"), TokenValue::Float64(0.5));
+    assert_eq!(single_value("1.5e3"), TokenValue::Float64(1500.0));
+    assert_eq!(single_value("2E-2"), TokenValue::Float64(0.02));
+    assert_eq!(single_value("1_000.25"), TokenValue::Float64(1000.25));
+    assert_eq!(single_value("0.5f32"), TokenValue::Float32(0.5));
+    assert_eq!(single_value("7f64"), TokenValue::Float64(7.0));
+    assert_eq!(single_error("1e400"), LexErrorKind::InvalidFloatLiteral("1e400".to_string()));
+}
+
+#[test]
+fn dots_after_integers_are_not_fractions() {
+    assert_eq!(types("1..n"), [TokenType::Int32Lit, TokenType::DotDot, TokenType::Ident]);
+    assert_eq!(types("0..=9"), [TokenType::Int32Lit, TokenType::DotDotEq, TokenType::Int32Lit]);
+    assert_eq!(types("3.x"), [TokenType::Int32Lit, TokenType::Dot, TokenType::Ident]);
+    assert_eq!(types("3._x"), [TokenType::Int32Lit, TokenType::Dot, TokenType::Ident]);
+    assert_eq!(types("x.5"), [TokenType::Ident, TokenType::Dot, TokenType::Int32Lit]);
+    assert_eq!(types("3. + 1"), [TokenType::FloatLit, TokenType::Plus, TokenType::Int32Lit]);
+}
+
+#[test]
+fn identifiers_are_compared_in_nfc() {
+    let decomposed = single_value("caf\u{65}\u{301}");
+    let composed = single_value("caf\u{e9}");
+    assert_eq!(decomposed, composed);
+    match composed {
+        TokenValue::Identifier(symbol) => assert_eq!(symbol.as_str(), "caf\u{e9}"),
+        value => panic!("expected an identifier, got {:?}", value),
+    }
+}
+
+#[test]
+fn identifiers_follow_unicode_xid_rules() {
+    assert_eq!(types("_x données αβγ x1"), [TokenType::Ident; 4]);
+    assert!(matches!(single_error("a\u{2603}"), LexErrorKind::UnexpectedCharacter('\u{2603}')));
+}
+
+#[test]
+fn every_lexical_error_is_reported_with_its_location() {
+    let errors = tokenize("$ let x = 1; @\nlet y = 99999999999;").unwrap_err();
+    let located: Vec<(LexErrorKind, usize, usize, Range<usize>)> =
+        errors.into_iter().map(|error| (error.kind, error.line, error.column, error.span)).collect();
+    assert_eq!(located, [
+        (LexErrorKind::UnexpectedCharacter('$'), 1, 1, 0..1),
+        (LexErrorKind::UnexpectedCharacter('@'), 1, 14, 13..14),
+        (LexErrorKind::IntegerOutOfRange("99999999999".to_string(), "i32".to_string()), 2, 9, 23..34),
+    ]);
+}
+
+#[test]
+fn operators_take_the_longest_match() {
+    use TokenType::*;
+    assert_eq!(
+        types("== != < <= > >= && || ! % ^ , . [ ]"),
+        [EqEq, NotEq, Lt, LtEq, Gt, GtEq, And, Or, Not, Percent, Caret, Comma, Dot, OpenBracket, CloseBracket],
+    );
+    assert_eq!(types("a<=-b"), [Ident, LtEq, Minus, Ident]);
+    assert_eq!(types("a==!b"), [Ident, EqEq, Not, Ident]);
+    assert_eq!(types("+=-=*=/=%=..=.."), [PlusEq, MinusEq, StarEq, SlashEq, PercentEq, DotDotEq, DotDot]);
+    assert_eq!(single_error("&"), LexErrorKind::UnexpectedCharacter('&'));
+    assert_eq!(single_error("|"), LexErrorKind::UnexpectedCharacter('|'));
+}
+
+#[test]
+fn doc_comments_attach_to_the_next_token() {
+    let tokens = tokenize("/// Adds one.\n///   Indented\nlet x = 1; // not a doc comment\n//// not one either\nreturn x;").unwrap();
+    let docs: Vec<Option<&str>> = tokens.iter().map(|token| token.doc.as_deref()).collect();
+    assert_eq!(docs, [Some("Adds one.\n  Indented"), None, None, None, None, None, None, None]);
+}
+
+#[test]
+fn block_comments_nest() {
+    assert_eq!(types("/* a /* b */ c */ x /**/ y"), [TokenType::Ident, TokenType::Ident]);
+
+    let errors = tokenize("let x /* a /* b */\n*/ = 1;\n/* never closed").unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, LexErrorKind::UnterminatedBlockComment);
+    assert_eq!((errors[0].line, errors[0].column), (3, 1));
+    assert_eq!(errors[0].span, 27..42);
+}
+
+#[test]
+fn strings_decode_their_escapes() {
+    let value = single_value(r#""a\nb\tc\\d\"e\u{48}\u{1F600}""#);
+    assert_eq!(value, TokenValue::Str(Cow::Owned("a\nb\tc\\d\"eH\u{1F600}".to_string())));
+    assert!(matches!(single_value(r#""plain text""#), TokenValue::Str(Cow::Borrowed("plain text"))));
+}
+
+#[test]
+fn raw_strings_borrow_their_text_verbatim() {
+    assert!(matches!(single_value(r#"r"a\nb \u{48}""#), TokenValue::Str(Cow::Borrowed(r"a\nb \u{48}"))));
+    assert!(matches!(single_value(r#"r"""#), TokenValue::Str(Cow::Borrowed(""))));
+}
+
+#[test]
+fn malformed_strings_are_reported_where_they_go_wrong() {
+    let cases = [
+        (r#"let s = "ab\q";"#, LexErrorKind::InvalidEscape("q".to_string()), 1, 12, 11..13),
+        (r#"let s = "\u{}";"#, LexErrorKind::InvalidUnicodeEscape(String::new()), 1, 10, 9..13),
+        (r#"let s = "x\u{D800}";"#, LexErrorKind::InvalidUnicodeEscape("D800".to_string()), 1, 11, 10..18),
+        (r#"let s = "\u{1234567}";"#, LexErrorKind::InvalidUnicodeEscape("1234567".to_string()), 1, 10, 9..20),
+        ("let s = 1;\n  let t = \"open", LexErrorKind::UnterminatedString, 2, 11, 21..26),
+        ("let s = r\"open\n", LexErrorKind::UnterminatedString, 1, 9, 8..15),
+    ];
+    for (source, kind, line, column, span) in cases {
+        let errors = tokenize(source).unwrap_err();
+        assert_eq!(errors.len(), 1, "for {:?}", source);
+        assert_eq!((&errors[0].kind, errors[0].line, errors[0].column, &errors[0].span), (&kind, line, column, &span), "for {:?}", source);
+    }
+}
+
+#[test]
+fn keywords_come_from_the_shared_table() {
+    for &(word, _type) in KEYWORDS {
+        assert_eq!(types(word), [_type], "for {:?}", word);
+        assert_eq!(TokenType::from_keyword(word), Some(_type));
+        if _type != TokenType::BoolLit {
+            assert_eq!(_type.keyword(), Some(word));
+        }
+    }
+    assert_eq!(single_value("true"), TokenValue::Boolean(true));
+    assert_eq!(single_value("false"), TokenValue::Boolean(false));
+    assert_eq!(TokenType::from_keyword("returns"), None);
+}
+
+#[test]
+fn reserved_words_cannot_be_identifiers() {
+    for &word in RESERVED_WORDS {
+        assert_eq!(single_error(word), LexErrorKind::ReservedWord(word.to_string()));
+    }
+    assert_eq!(types("loops matched"), [TokenType::Ident, TokenType::Ident]);
+}
+
+#[test]
+fn character_literals_hold_one_code_point() {
+    assert_eq!(types("'a' '\\n'"), [TokenType::CharLit, TokenType::CharLit]);
+    assert_eq!(single_value("'a'"), TokenValue::Char('a'));
+    assert_eq!(single_value("'\\n'"), TokenValue::Char('\n'));
+    assert_eq!(single_value("'\\''"), TokenValue::Char('\''));
+    assert_eq!(single_value("'\\u{1F600}'"), TokenValue::Char('\u{1F600}'));
+    assert_eq!(single_value("'é'"), TokenValue::Char('é'));
+
+    assert_eq!(single_error("''"), LexErrorKind::EmptyCharLiteral);
+    assert_eq!(single_error("'ab'"), LexErrorKind::MultipleCharsInLiteral);
+    assert_eq!(single_error("'a"), LexErrorKind::UnterminatedChar);
+    assert_eq!(single_error("'a\nx"), LexErrorKind::UnterminatedChar);
+}
@@ -0,0 +1,58 @@
+use std::fs;
+use std::path::Path;
+
+use fikra::fikra_entities::{TokenType, TriviaKind};
+use fikra::fikra_tokenization::tokenization::tokens::{render, tokenize_lossless};
+
+fn assert_round_trip(source: &str) {
+    let (tokens, _) = tokenize_lossless(source);
+    assert_eq!(render(source, &tokens), source);
+    assert_eq!(tokens.last().map(|token| token._type), Some(TokenType::EOF));
+}
+
+#[test]
+fn sample_programs_round_trip() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let mut samples = 0;
+
+    for entry in fs::read_dir(root).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "fkr") {
+            assert_round_trip(&fs::read_to_string(&path).unwrap());
+            samples += 1;
+        }
+    }
+    assert!(samples > 0, "no .fkr sample programs found");
+}
+
+#[test]
+fn comments_strings_and_unicode_round_trip() {
+    assert_round_trip(
+        "/// doc\r\n/// more\nlet jina\u{0301} = \"a\\tb\" ;   // trailing\n\t/* outer /* inner */ */\n\nreturn r\"raw\" ; \n",
+    );
+    assert_round_trip("");
+    assert_round_trip("   \n\n");
+}
+
+#[test]
+fn text_with_lexical_errors_round_trips() {
+    let source = "let x = 5 $ 3;\nlet y = 99999999999;\n/* never closed";
+    let (tokens, errors) = tokenize_lossless(source);
+
+    assert_eq!(errors.len(), 3);
+    assert_eq!(render(source, &tokens), source);
+}
+
+#[test]
+fn trailing_trivia_stops_at_end_of_line() {
+    let source = "let x = 1; // one\n  // two\nreturn x;";
+    let (tokens, _) = tokenize_lossless(source);
+
+    let semi = tokens.iter().find(|token| token._type == TokenType::Semi).unwrap();
+    let trailing: Vec<_> = semi.trailing_trivia.iter().map(|piece| (piece.kind, piece.text)).collect();
+    assert_eq!(trailing, [(TriviaKind::Whitespace, " "), (TriviaKind::LineComment, "// one"), (TriviaKind::Whitespace, "\n")]);
+
+    let ret = tokens.iter().find(|token| token._type == TokenType::Return).unwrap();
+    let leading: Vec<_> = ret.leading_trivia.iter().map(|piece| piece.text).collect();
+    assert_eq!(leading, ["  ", "// two", "\n"]);
+}
@@ -0,0 +1,114 @@
+use fikra::fikra_entities::TokenType;
+use fikra::fikra_errors::{Construct, Expected, ParseError, SyntaxError};
+use fikra::fikra_parser::node::{AstArena, NodeStmt, StmtVariant};
+use fikra::fikra_parser::parser::Parser;
+
+fn syntax_errors(source: &str) -> Vec<SyntaxError> {
+    let ast_arena = AstArena::new();
+    match Parser::new(source, &ast_arena).parse_prog() {
+        Err(ParseError::Syntax(errors)) => errors,
+        Err(error) => panic!("expected a syntax error, got {}", error),
+        Ok(_) => panic!("expected a syntax error in {:?}", source),
+    }
+}
+
+fn syntax_error(source: &str) -> SyntaxError {
+    let mut errors = syntax_errors(source);
+    assert_eq!(errors.len(), 1, "for {:?}", source);
+    errors.remove(0)
+}
+
+#[test]
+fn missing_semicolon_points_at_the_next_token() {
+    let error = syntax_error("let x = 1;\nlet y = 2;\nlet z = x + y }");
+    assert_eq!(error.expected, [Expected::Token(TokenType::Semi)]);
+    assert_eq!(error.found, Some(TokenType::CloseBrace));
+    assert_eq!(error.construct, Construct::LetStatement);
+    assert_eq!((error.line, error.column), (3, 15));
+    assert_eq!(error.to_string(), "expected `;` after let statement at 3:15, found `}`");
+}
+
+#[test]
+fn errors_name_what_was_expected_and_where() {
+    let cases = [
+        ("return 1 + ;", "expected expression in return statement at 1:12, found `;`"),
+        ("let = 3;", "expected identifier in let statement at 1:5, found `=`"),
+        ("let x 3;", "expected `=` in let statement at 1:7, found integer literal"),
+        ("return (1 + 2;", "expected `)` in parenthesized expression at 1:14, found `;`"),
+        ("{ let a = 1;", "expected one of statement or `}` in scope at 1:13, found end of input"),
+        (")", "expected statement in program at 1:1, found `)`"),
+        ("let x =", "expected expression in let statement at 1:8, found end of input"),
+        ("if x return 1;", "expected `{` in if statement at 1:6, found `return`"),
+        ("for i 0..3 { }", "expected `in` in for statement at 1:7, found integer literal"),
+        ("for i in 0 { }", "expected one of `..` or `..=` in for statement at 1:12, found `{`"),
+        ("fn f(a b) { }", "expected one of `,` or `)` in function declaration at 1:8, found identifier"),
+        ("return f(1;", "expected one of `,` or `)` in function call at 1:11, found `;`"),
+        ("f(1) x", "expected `;` after call statement at 1:6, found identifier"),
+        ("f 1;", "expected one of `=`, `+=`, `-=`, `*=`, `/=`, `%=` or `(` in assignment at 1:3, found integer literal"),
+        ("let a = [1, 2;", "expected one of `,` or `]` in array literal at 1:14, found `;`"),
+        ("let a = [1 2];", "expected one of `,`, `;` or `]` in array literal at 1:12, found integer literal"),
+        ("return a[1;", "expected `]` in index expression at 1:11, found `;`"),
+        ("if x { } else return 1;", "expected `{` in if statement at 1:15, found `return`"),
+    ];
+    for (source, message) in cases {
+        assert_eq!(syntax_error(source).to_string(), message, "for {:?}", source);
+    }
+}
+
+#[test]
+fn recovery_reports_every_bad_statement() {
+    let source = "let a = 1\nlet b = ;\nreturn a + b;\n{ let c = ) ; let d = 2; }\nlet e = 3 }\nreturn e;";
+    let messages: Vec<String> = syntax_errors(source).iter().map(|e| e.to_string()).collect();
+    assert_eq!(messages, [
+        "expected `;` after let statement at 2:1, found `let`",
+        "expected expression in let statement at 2:9, found `;`",
+        "expected expression in let statement at 4:11, found `)`",
+        "expected `;` after let statement at 5:11, found `}`",
+    ]);
+}
+
+#[test]
+fn partial_program_keeps_the_valid_statements() {
+    let ast_arena = AstArena::new();
+    let partial = Parser::new("let a = 1;\nlet = 2;\n{ return ; let b = a; }\nreturn a;", &ast_arena).parse_prog_partial();
+    assert_eq!(partial.syntax_errors.len(), 2);
+    assert!(partial.lex_errors.is_empty());
+
+    let kinds: Vec<&str> = partial.prog.statements.iter().map(|stmt| statement_kind(stmt)).collect();
+    assert_eq!(kinds, ["let", "error", "scope", "return"]);
+    match &partial.prog.statements[2].variant {
+        StmtVariant::VariantThree(scope) => {
+            let inner: Vec<&str> = scope.statements.iter().map(|stmt| statement_kind(stmt)).collect();
+            assert_eq!(inner, ["error", "let"]);
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn unclosed_scope_keeps_its_statements() {
+    let ast_arena = AstArena::new();
+    let partial = Parser::new("{ let a = 1; return a;", &ast_arena).parse_prog_partial();
+    assert_eq!(partial.syntax_errors.len(), 1);
+    match &partial.prog.statements[0].variant {
+        StmtVariant::VariantThree(scope) => assert_eq!(scope.statements.len(), 2),
+        _ => panic!("expected a scope"),
+    }
+}
+
+fn statement_kind(stmt: &NodeStmt) -> &'static str {
+    match stmt.variant {
+        StmtVariant::VariantOne(_) => "return",
+        StmtVariant::VariantTwo(_) => "let",
+        StmtVariant::VariantThree(_) => "scope",
+        StmtVariant::VariantFour(_) => "error",
+        StmtVariant::VariantFive(_) => "if",
+        StmtVariant::VariantSix(_) => "while",
+        StmtVariant::VariantSeven(_) => "break",
+        StmtVariant::VariantEight(_) => "continue",
+        StmtVariant::VariantNine(_) => "for",
+        StmtVariant::VariantTen(_) => "assign",
+        StmtVariant::VariantEleven(_) => "fn",
+        StmtVariant::VariantTwelve(_) => "call",
+    }
+}
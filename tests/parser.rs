@@ -0,0 +1,218 @@
+use fikra::fikra_entities::{Token, TokenType, TokenValue};
+use fikra::fikra_parser::node::{AstArena, ExprVar, NodeBinExprVariant, NodeExpr, NodeExprArrayVariant, NodeUnaryExprVariant, StmtVariant};
+use fikra::fikra_parser::parser::Parser;
+
+// Parses `return <source>;` and shows the returned expression with every operation in prefix form
+fn expression(source: &str) -> String {
+    let source = format!("return {};", source);
+    let ast_arena = AstArena::new();
+    let prog = Parser::new(&source, &ast_arena).parse_prog().unwrap_or_else(|error| panic!("{}", error));
+    match &prog.statements[0].variant {
+        StmtVariant::VariantOne(stmt) => show(stmt.expr),
+        _ => unreachable!(),
+    }
+}
+
+fn name(token: &Token) -> &'static str {
+    match &token.value {
+        Some(TokenValue::Identifier(symbol)) => symbol.as_str(),
+        _ => panic!("expected an identifier"),
+    }
+}
+
+fn show(expr: &NodeExpr) -> String {
+    match &expr.variant {
+        ExprVar::VariantOne(node) => match node.int_lit.value.as_ref().and_then(TokenValue::as_integer) {
+            Some(n) => n.to_string(),
+            None => panic!("expected an integer literal"),
+        },
+        ExprVar::VariantTwo(node) => name(&node.ident).to_string(),
+        ExprVar::VariantThree(node) => {
+            let (op, lhs, rhs) = match &node.variant {
+                NodeBinExprVariant::VariantOne(n) => ("+", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantTwo(n) => ("*", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantThree(n) => ("-", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantFour(n) => ("/", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantFive(n) => ("==", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantSix(n) => ("!=", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantSeven(n) => ("<", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantEight(n) => ("<=", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantNine(n) => (">", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantTen(n) => (">=", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantEleven(n) => ("&&", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantTwelve(n) => ("||", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantThirteen(n) => ("^", n.lhs, n.rhs),
+                NodeBinExprVariant::VariantFourteen(n) => ("%", n.lhs, n.rhs),
+            };
+            format!("({} {} {})", op, show(lhs), show(rhs))
+        },
+        ExprVar::VariantFour(node) => show(node.expr),
+        ExprVar::VariantFive(node) => match &node.variant {
+            NodeUnaryExprVariant::VariantOne(n) => format!("(neg {})", show(n.expr)),
+            NodeUnaryExprVariant::VariantTwo(n) => format!("(! {})", show(n.expr)),
+        },
+        ExprVar::VariantSix(node) => {
+            let args: Vec<String> = node.args.iter().map(|arg| format!(" {}", show(arg))).collect();
+            format!("({}{})", name(&node.ident), args.concat())
+        },
+        ExprVar::VariantSeven(node) => match &node.variant {
+            NodeExprArrayVariant::VariantOne(list) => {
+                let elements: Vec<String> = list.elements.iter().map(|element| show(element)).collect();
+                format!("[{}]", elements.join(" "))
+            },
+            NodeExprArrayVariant::VariantTwo(repeat) => format!("[{}; {}]", show(repeat.value), show(repeat.count)),
+        },
+        ExprVar::VariantEight(node) => format!("{}[{}]", name(&node.ident), show(node.index)),
+    }
+}
+
+#[test]
+fn logical_and_comparison_operators_bind_looser_than_arithmetic() {
+    assert_eq!(expression("a || b && c == d < e + f * g"), "(|| a (&& b (== c (< d (+ e (* f g))))))");
+    assert_eq!(expression("a < b == c > d"), "(== (< a b) (> c d))");
+    assert_eq!(expression("a && b || c && d"), "(|| (&& a b) (&& c d))");
+}
+
+#[test]
+fn operators_of_equal_precedence_group_to_the_left() {
+    assert_eq!(expression("a - b - c"), "(- (- a b) c)");
+    assert_eq!(expression("a / b * c"), "(* (/ a b) c)");
+    assert_eq!(expression("a || b || c"), "(|| (|| a b) c)");
+    assert_eq!(expression("a - (b - c)"), "(- a (- b c))");
+}
+
+#[test]
+fn unary_operators_bind_tighter_than_binary_ones() {
+    assert_eq!(expression("-a * b"), "(* (neg a) b)");
+    assert_eq!(expression("!a == b"), "(== (! a) b)");
+    assert_eq!(expression("a - -b"), "(- a (neg b))");
+    assert_eq!(expression("!-a"), "(! (neg a))");
+    assert_eq!(expression("-(a + b)"), "(neg (+ a b))");
+}
+
+#[test]
+fn exponentiation_groups_to_the_right_above_unary_minus() {
+    assert_eq!(expression("2 ^ 3 ^ 2"), "(^ 2 (^ 3 2))");
+    assert_eq!(expression("-x ^ 2"), "(^ (neg x) 2)");
+    assert_eq!(expression("a * b ^ c"), "(* a (^ b c))");
+    assert_eq!(expression("(2 ^ 3) ^ 2"), "(^ (^ 2 3) 2)");
+}
+
+#[test]
+fn else_if_chains_nest_in_the_else_branch() {
+    let ast_arena = AstArena::new();
+    let prog = Parser::new("if a { } else if b { return 1; } else { return 2; }", &ast_arena).parse_prog().unwrap();
+    let outer = match &prog.statements[0].variant {
+        StmtVariant::VariantFive(stmt) => stmt,
+        _ => panic!("expected an if statement"),
+    };
+    assert_eq!(show(outer.condition), "a");
+    let inner = match outer.else_stmt.map(|stmt| &stmt.variant) {
+        Some(StmtVariant::VariantFive(stmt)) => stmt,
+        _ => panic!("expected `else if` to hold an if statement"),
+    };
+    assert_eq!(show(inner.condition), "b");
+    assert_eq!(inner.body.statements.len(), 1);
+    match inner.else_stmt.map(|stmt| &stmt.variant) {
+        Some(StmtVariant::VariantThree(scope)) => assert_eq!(scope.statements.len(), 1),
+        _ => panic!("expected `else` to hold a scope"),
+    }
+}
+
+#[test]
+fn for_loops_keep_their_range_and_step() {
+    let ast_arena = AstArena::new();
+    let prog = Parser::new("for i in 0..=n - 1 step 2 { }\nfor j in a..b { }", &ast_arena).parse_prog().unwrap();
+    match &prog.statements[0].variant {
+        StmtVariant::VariantNine(stmt) => {
+            assert_eq!(name(&stmt.ident), "i");
+            assert_eq!((show(stmt.start), show(stmt.end)), ("0".to_string(), "(- n 1)".to_string()));
+            assert!(stmt.inclusive);
+            assert_eq!(stmt.step.map(show).as_deref(), Some("2"));
+        },
+        _ => panic!("expected a for statement"),
+    }
+    match &prog.statements[1].variant {
+        StmtVariant::VariantNine(stmt) => {
+            assert_eq!((show(stmt.start), show(stmt.end)), ("a".to_string(), "b".to_string()));
+            assert!(!stmt.inclusive);
+            assert!(stmt.step.is_none());
+        },
+        _ => panic!("expected a for statement"),
+    }
+}
+
+#[test]
+fn compound_assignments_become_plain_ones() {
+    let ast_arena = AstArena::new();
+    let prog = Parser::new("let mut x = 1;\nx += 2 * y;\nx -= y - 1;\nx %= 3;\nx = 4;", &ast_arena).parse_prog().unwrap();
+    match &prog.statements[0].variant {
+        StmtVariant::VariantTwo(stmt) => assert!(stmt.mutable),
+        _ => panic!("expected a let statement"),
+    }
+    let assignments: Vec<String> = prog.statements[1..].iter().map(|stmt| match &stmt.variant {
+        StmtVariant::VariantTen(stmt) => format!("{} = {}", name(&stmt.ident), show(stmt.expr)),
+        _ => panic!("expected an assignment"),
+    }).collect();
+    assert_eq!(assignments, ["x = (+ x (* 2 y))", "x = (- x (- y 1))", "x = (% x 3)", "x = 4"]);
+}
+
+#[test]
+fn calls_take_any_expressions_as_arguments() {
+    assert_eq!(expression("f()"), "(f)");
+    assert_eq!(expression("f(a, g(b) + 1)"), "(f a (+ (g b) 1))");
+    assert_eq!(expression("-f(a) * 2"), "(* (neg (f a)) 2)");
+}
+
+#[test]
+fn array_literals_and_indexing_take_expressions() {
+    assert_eq!(expression("[]"), "[]");
+    assert_eq!(expression("[1, a + 1]"), "[1 (+ a 1)]");
+    assert_eq!(expression("[0; 4 * 4]"), "[0; (* 4 4)]");
+    assert_eq!(expression("a[i + 1] * 2"), "(* a[(+ i 1)] 2)");
+}
+
+#[test]
+fn boolean_literals_are_expressions() {
+    assert_eq!(expression("true && !false"), "(&& 1 (! 0))");
+
+    let ast_arena = AstArena::new();
+    let prog = Parser::new("while true { break; }\nif false { }", &ast_arena).parse_prog().unwrap_or_else(|error| panic!("{}", error));
+    match &prog.statements[0].variant {
+        StmtVariant::VariantSix(stmt) => {
+            assert_eq!(show(stmt.condition), "1");
+            assert_eq!(stmt.body.statements.len(), 1);
+        },
+        _ => panic!("expected a while statement"),
+    }
+    assert!(matches!(prog.statements[1].variant, StmtVariant::VariantFive(_)));
+}
+
+#[test]
+fn calls_can_stand_alone_as_statements() {
+    let ast_arena = AstArena::new();
+    let prog = Parser::new("f(1, g(x));\nx = f();", &ast_arena).parse_prog().unwrap_or_else(|error| panic!("{}", error));
+    match &prog.statements[0].variant {
+        StmtVariant::VariantTwelve(stmt) => {
+            assert_eq!(name(&stmt.call.ident), "f");
+            let args: Vec<String> = stmt.call.args.iter().map(|arg| show(arg)).collect();
+            assert_eq!(args, ["1", "(g x)"]);
+        },
+        _ => panic!("expected a call statement"),
+    }
+    assert!(matches!(prog.statements[1].variant, StmtVariant::VariantTen(_)));
+}
+
+#[test]
+fn compound_assignments_to_elements_keep_their_operator() {
+    let ast_arena = AstArena::new();
+    let prog = Parser::new("a[i + 1] += 2 * y;\na[0] = 1;", &ast_arena).parse_prog().unwrap_or_else(|error| panic!("{}", error));
+    let assignments: Vec<(String, Option<TokenType>, String)> = prog.statements.iter().map(|stmt| match &stmt.variant {
+        StmtVariant::VariantTen(stmt) => (stmt.index.map(show).unwrap(), stmt.op, show(stmt.expr)),
+        _ => panic!("expected an assignment"),
+    }).collect();
+    assert_eq!(assignments, [
+        ("(+ i 1)".to_string(), Some(TokenType::Plus), "(* 2 y)".to_string()),
+        ("0".to_string(), None, "1".to_string()),
+    ]);
+}
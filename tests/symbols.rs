@@ -0,0 +1,15 @@
+use fikra::fikra_symbols::Symbol;
+
+#[test]
+fn interning_is_idempotent_and_round_trips() {
+    let first = Symbol::intern("symbol_test_name");
+    assert_eq!(Symbol::intern("symbol_test_name"), first);
+    assert_eq!(Symbol::intern(&String::from("symbol_test_name")), first);
+    assert_ne!(Symbol::intern("symbol_test_other"), first);
+    assert_ne!(Symbol::intern("Symbol_test_name"), first);
+
+    for name in ["symbol_test_name", "données", "αβγ", "_"] {
+        assert_eq!(Symbol::intern(name).as_str(), name);
+        assert_eq!(Symbol::intern(name).to_string(), name);
+    }
+}